@@ -0,0 +1,198 @@
+use super::{Heuristic, Limits, SearchType};
+use crate::puzzle;
+
+use priority_queue::PriorityQueue;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+/// Weight under which the next search is done with a weight of 1.
+const LAST_WEIGHT: f32 = 1.1;
+
+/// States met by the searches, with their distance to the goal.
+struct Search {
+	states: HashMap<puzzle::Cells, (puzzle::State, i32)>,
+	open_queue: PriorityQueue<puzzle::Cells, Reverse<i32>>,
+	closed_set: HashSet<puzzle::Cells>,
+	inconsistent: HashSet<puzzle::Cells>,
+	goal: puzzle::State,
+	distance: Heuristic,
+	score: SearchType,
+}
+
+impl Search {
+	fn priority(&self, cells: &puzzle::Cells, weight_scaled: i32) -> i32 {
+		let (state, h) = &self.states[cells];
+		return (self.score)(*state.cost(), *h, weight_scaled);
+	}
+
+	fn goal_cost(&self) -> Option<i32> {
+		return self.states.get(self.goal.packed()).map(|(state, _)| *state.cost());
+	}
+
+	/// Expand states until none can lead to a cheaper goal with the current weight.
+	fn improve(
+		&mut self,
+		weight_scaled: i32,
+		limits: &Limits,
+		solution: &mut puzzle::Solution,
+	) -> Option<puzzle::Limit> {
+		while let Some((_, Reverse(top))) = self.open_queue.peek() {
+			if self.goal_cost().is_some_and(|cost| (self.score)(cost, 0, weight_scaled) <= *top) {
+				return None;
+			}
+			let (cells, _) = self.open_queue.pop().unwrap();
+			let current_state = self.states[&cells].0.clone();
+			for mut neighbor in current_state.neighbors() {
+				*(neighbor.cost_mut()) = current_state.cost() + 1;
+				let known =
+					self.states.get(neighbor.packed()).map(|(state, h)| (*state.cost(), *h));
+				if known.is_some_and(|(cost, _)| cost <= *neighbor.cost()) {
+					continue;
+				}
+				let h = known.map_or_else(|| (self.distance)(&neighbor, &self.goal), |(_, h)| h);
+				let neighbor_cells = neighbor.packed().clone();
+				self.states.insert(neighbor_cells.clone(), (neighbor, h));
+				if self.closed_set.contains(&neighbor_cells) {
+					self.inconsistent.insert(neighbor_cells);
+				} else {
+					let priority = self.priority(&neighbor_cells, weight_scaled);
+					self.open_queue.push(neighbor_cells, Reverse(priority));
+				}
+			}
+			self.closed_set.insert(cells);
+			solution.update_complexity(self.states.len());
+			solution.update_frontier(self.open_queue.len());
+			if let Some(limit) = limits.reached(solution) {
+				return Some(limit);
+			}
+		}
+		return None;
+	}
+
+	/// States from the start to the goal, following the moves that reached each state.
+	fn path(&self) -> Vec<puzzle::State> {
+		let mut path = Vec::new();
+		let mut current_state = self.states[self.goal.packed()].0.clone();
+		while let Some(prev) = current_state.predecessor() {
+			let next = self.states[&prev].0.clone();
+			path.push(current_state);
+			current_state = next;
+		}
+		path.push(current_state);
+		path.reverse();
+		return path;
+	}
+
+	/// Highest ratio between the cost of the goal found and the optimal one.
+	fn bound(&self, weight: f32) -> f32 {
+		let goal_cost = self.goal_cost().unwrap();
+		let lowest = self
+			.open_queue
+			.iter()
+			.map(|(cells, _)| cells)
+			.chain(self.inconsistent.iter())
+			.map(|cells| {
+				let (state, h) = &self.states[cells];
+				state.cost() + h
+			})
+			.min();
+		return match lowest {
+			Some(lowest) if lowest > 0 => weight.min(goal_cost as f32 / lowest as f32).max(1.0),
+			_ => 1.0,
+		};
+	}
+}
+
+/// Anytime repairing A* search from `start` to `goal`, publishing better and better solutions.
+///
+/// A first solution is searched with `weight`, then the weight is lowered and the search goes on
+/// from the states already met, only expanding again the ones whose cost decreased. Every time a
+/// shorter solution is found or its bound tightens, `publish` is given the best solution with a
+/// bound on how many times longer than the optimal one it can be. The search ends once the bound
+/// reaches 1, or when a limit is reached, returning the last solution found, aborted by the limit
+/// in the second case. With the best first search type and a `distance` never overestimating, the
+/// bounds are proven.
+pub fn anytime<F: FnMut(&puzzle::Solution, f32)>(
+	mut start: puzzle::State,
+	goal: puzzle::State,
+	distance: Heuristic,
+	score: SearchType,
+	weight: f32,
+	limits: &Limits,
+	mut publish: F,
+) -> puzzle::Solution {
+	let mut solution = puzzle::Solution::new();
+	let mut best: Option<Vec<puzzle::State>> = None;
+	let mut best_bound = f32::INFINITY;
+	let mut weight = weight.max(1.0);
+
+	*(start.cost_mut()) = 0;
+	let start_cells = start.packed().clone();
+	let h = distance(&start, &goal);
+	let mut search = Search {
+		states: HashMap::new(),
+		open_queue: PriorityQueue::new(),
+		closed_set: HashSet::new(),
+		inconsistent: HashSet::new(),
+		goal,
+		distance,
+		score,
+	};
+	search.states.insert(start_cells.clone(), (start, h));
+	let priority = search.priority(&start_cells, (100.0 * weight).round() as i32);
+	search.open_queue.push(start_cells, Reverse(priority));
+	loop {
+		let weight_scaled = (100.0 * weight).round() as i32;
+		let limit = search.improve(weight_scaled, limits, &mut solution);
+		if let Some(cost) = search.goal_cost() {
+			let bound = search.bound(weight);
+			if best.as_ref().is_none_or(|path| path.len() as i32 - 1 > cost) {
+				best = Some(search.path());
+			}
+			if bound < best_bound {
+				let path = best.clone().unwrap();
+				publish(&solution.clone().build_from_path(path), bound);
+				best_bound = bound;
+			}
+			if bound <= 1.0 {
+				break;
+			}
+		}
+		if let Some(limit) = limit {
+			return match best {
+				Some(path) => solution.build_from_path(path).abort(limit),
+				None => solution.abort(limit),
+			};
+		}
+		if weight <= 1.0 || search.open_queue.is_empty() && search.inconsistent.is_empty() {
+			break;
+		}
+		weight = if weight < LAST_WEIGHT { 1.0 } else { 1.0 + (weight - 1.0) / 2.0 };
+		let weight_scaled = (100.0 * weight).round() as i32;
+		let inconsistent: Vec<puzzle::Cells> = search.inconsistent.drain().collect();
+		let waiting: Vec<puzzle::Cells> =
+			search.open_queue.iter().map(|(cells, _)| cells.clone()).chain(inconsistent).collect();
+		search.open_queue.clear();
+		for cells in waiting {
+			let priority = search.priority(&cells, weight_scaled);
+			search.open_queue.push(cells, Reverse(priority));
+		}
+		search.closed_set.clear();
+	}
+	return match best {
+		Some(path) => solution.build_from_path(path),
+		None => solution,
+	};
+}
+
+/// Anytime repairing A* search, returning only its last solution, see [`anytime`].
+pub fn ara_star(
+	start: puzzle::State,
+	goal: puzzle::State,
+	distance: Heuristic,
+	score: SearchType,
+	weight: f32,
+	limits: &Limits,
+) -> puzzle::Solution {
+	return anytime(start, goal, distance, score, weight, limits, |_, _| {});
+}
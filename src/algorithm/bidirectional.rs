@@ -0,0 +1,245 @@
+use super::{Heuristic, Limits, SearchType};
+use crate::puzzle;
+
+use priority_queue::PriorityQueue;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
+
+type Meeting = (i32, puzzle::State, puzzle::State);
+
+struct Frontier {
+	open_queue: PriorityQueue<puzzle::State, Reverse<i32>>,
+	closed_set: HashSet<puzzle::State>,
+	open_costs: BTreeMap<i32, usize>,
+	target: puzzle::State,
+}
+
+impl Frontier {
+	fn new(
+		mut origin: puzzle::State,
+		target: puzzle::State,
+		distance: Heuristic,
+		score: SearchType,
+		weight_scaled: i32,
+	) -> Self {
+		let mut frontier = Frontier {
+			open_queue: PriorityQueue::new(),
+			closed_set: HashSet::new(),
+			open_costs: BTreeMap::new(),
+			target,
+		};
+		let o_score = score(0, distance(&origin, &frontier.target), weight_scaled);
+		*(origin.score_mut()) = o_score;
+		frontier.push(origin, o_score);
+		return frontier;
+	}
+
+	fn push(&mut self, state: puzzle::State, state_score: i32) {
+		if let Some((replaced, _)) = self.open_queue.remove(&state) {
+			self.forget_cost(*replaced.cost());
+		}
+		*self.open_costs.entry(*state.cost()).or_insert(0) += 1;
+		self.open_queue.push(state, Reverse(state_score));
+	}
+
+	fn pop(&mut self) -> Option<puzzle::State> {
+		let (state, _) = self.open_queue.pop()?;
+		self.forget_cost(*state.cost());
+		return Some(state);
+	}
+
+	fn forget_cost(&mut self, cost: i32) {
+		if let Some(count) = self.open_costs.get_mut(&cost) {
+			*count -= 1;
+			if *count == 0 {
+				self.open_costs.remove(&cost);
+			}
+		}
+	}
+
+	fn min_cost(&self) -> i32 {
+		return self.open_costs.keys().next().copied().unwrap_or(0);
+	}
+
+	fn top(&self) -> Option<i32> {
+		return self.open_queue.peek().map(|(_, Reverse(s))| *s);
+	}
+
+	fn len(&self) -> usize {
+		return self.open_queue.len() + self.closed_set.len();
+	}
+
+	fn reached(&self, cells: &puzzle::Cells) -> Option<&puzzle::State> {
+		return self
+			.closed_set
+			.get(cells)
+			.or_else(|| self.open_queue.get(cells).map(|(state, _)| state));
+	}
+
+	fn expand(
+		&mut self,
+		other: &Frontier,
+		distance: Heuristic,
+		score: SearchType,
+		weight_scaled: i32,
+	) -> Option<Meeting> {
+		let current_state = self.pop()?;
+		let mut meeting: Option<Meeting> = None;
+		for mut neighbor in current_state.neighbors() {
+			*(neighbor.cost_mut()) = current_state.cost() + 1;
+			let n_score = score(*neighbor.cost(), distance(&neighbor, &self.target), weight_scaled);
+			*(neighbor.score_mut()) = n_score;
+			if let Some(met) = other.reached(neighbor.packed()) {
+				let cost = neighbor.cost() + met.cost();
+				if meeting.as_ref().is_none_or(|(best, _, _)| cost < *best) {
+					meeting = Some((cost, neighbor.clone(), met.clone()));
+				}
+			}
+			if !self.closed_set.contains(neighbor.packed()) {
+				if let Some((state_existing, _)) = self.open_queue.get(&neighbor) {
+					if state_existing.cost() > neighbor.cost() {
+						self.push(neighbor, n_score);
+					}
+				} else {
+					self.push(neighbor, n_score);
+				}
+			}
+		}
+		self.closed_set.insert(current_state);
+		return meeting;
+	}
+
+	fn path_to(&self, last_state: puzzle::State) -> Vec<puzzle::State> {
+		let mut path = Vec::new();
+		let mut current_state = last_state;
+		while let Some(prev) = current_state.predecessor() {
+			let next = self.closed_set.get(&prev).unwrap().clone();
+			path.push(current_state);
+			current_state = next;
+		}
+		path.push(current_state);
+		return path;
+	}
+}
+
+/// Bidirectional A* search, expanding states from both `start` and `goal` until they meet.
+///
+/// Each side estimates its distance to the origin of the other side, and the smallest side is
+/// expanded first. The search stops once no better meeting can be found according to the scores
+/// of both sides or the smallest costs left on both sides, so using the best first search type
+/// with a weight of 1 gives optimal solutions.
+pub fn bidirectional(
+	start: puzzle::State,
+	goal: puzzle::State,
+	distance: Heuristic,
+	score: SearchType,
+	weight: f32,
+	limits: &Limits,
+) -> puzzle::Solution {
+	let mut solution = puzzle::Solution::new();
+	let weight_scaled: i32 = (100.0 * weight).round() as i32;
+
+	if start == goal {
+		return solution.build_from_path(vec![start]);
+	}
+	let mut forward = Frontier::new(start.clone(), goal.clone(), distance, score, weight_scaled);
+	let mut backward = Frontier::new(goal, start, distance, score, weight_scaled);
+	let mut meeting: Option<Meeting> = None;
+	while let (Some(top_forward), Some(top_backward)) = (forward.top(), backward.top()) {
+		if let Some((cost, _, _)) = meeting {
+			if score(cost, 0, weight_scaled) <= top_forward.max(top_backward)
+				|| cost <= forward.min_cost() + backward.min_cost() + 1
+			{
+				break;
+			}
+		}
+		let forward_turn = forward.open_queue.len() <= backward.open_queue.len();
+		let new_meeting = if forward_turn {
+			forward.expand(&backward, distance, score, weight_scaled)
+		} else {
+			backward
+				.expand(&forward, distance, score, weight_scaled)
+				.map(|(cost, b, f)| (cost, f, b))
+		};
+		if let Some((cost, f, b)) = new_meeting {
+			if meeting.as_ref().is_none_or(|(best, _, _)| cost < *best) {
+				meeting = Some((cost, f, b));
+			}
+		}
+		solution.update_complexity(forward.len() + backward.len());
+		solution.update_frontier(forward.open_queue.len() + backward.open_queue.len());
+		if let Some(limit) = limits.reached(&solution) {
+			return solution.abort(limit);
+		}
+	}
+	return match meeting {
+		Some((_, forward_side, backward_side)) => {
+			let mut path = forward.path_to(forward_side);
+			path.reverse();
+			for next in backward.path_to(backward_side).into_iter().skip(1) {
+				let current_state = path.last().unwrap();
+				let mut step = current_state
+					.neighbors()
+					.into_iter()
+					.find(|n| n == &next)
+					.unwrap();
+				*(step.cost_mut()) = current_state.cost() + 1;
+				path.push(step);
+			}
+			solution.build_from_path(path)
+		}
+		None => solution,
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::heuristic::{self, tests::distances};
+	use super::super::search_type::best_first;
+	use super::*;
+	use crate::{generation, verification};
+
+	use rand::rngs::StdRng;
+	use rand::SeedableRng;
+
+	#[test]
+	fn finds_optimal_solutions() {
+		let mut rng = StdRng::seed_from_u64(42);
+		for goal in [puzzle::State::goal(3), puzzle::State::goal_rectangle(4, 2)] {
+			let distances = distances(&goal);
+			for _ in 0..10 {
+				let start = generation::uniform(&goal, true, &mut rng);
+				let solution = bidirectional(
+					start.clone(),
+					goal.clone(),
+					heuristic::manhattan,
+					best_first,
+					1.0,
+					&Limits::none(),
+				);
+				let moves: Vec<verification::Move> = solution
+					.directions()
+					.unwrap()
+					.into_iter()
+					.map(verification::Move::Direction)
+					.collect();
+				assert_eq!(moves.len() as i32, distances[start.packed()]);
+				assert!(verification::replay(&start, &goal, &moves).unwrap().solved);
+			}
+		}
+	}
+
+	#[test]
+	fn solved_start() {
+		let goal = puzzle::State::goal(3);
+		let solution = bidirectional(
+			goal.clone(),
+			goal,
+			heuristic::manhattan,
+			best_first,
+			1.0,
+			&Limits::none(),
+		);
+		assert_eq!(solution.directions().unwrap().len(), 0);
+	}
+}
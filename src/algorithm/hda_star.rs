@@ -0,0 +1,267 @@
+use super::{Heuristic, Limits, SearchType};
+use crate::puzzle;
+
+use priority_queue::PriorityQueue;
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// Time an idle worker waits for a state before checking again whether the search ended.
+const IDLE_WAIT: Duration = Duration::from_millis(1);
+/// Number of states a worker expands between two reports of its complexity.
+const CHECK_INTERVAL: usize = 256;
+
+/// Everything the workers share: the search settings, the channels to send states to their
+/// owner and the counters telling when the search is over.
+///
+/// The workers count their own complexity and only add it to `expanded` and `stored` every
+/// `CHECK_INTERVAL` expansions, checking the limits against these totals.
+struct Shared<'a> {
+	goal: puzzle::State,
+	distance: Heuristic,
+	score: SearchType,
+	weight_scaled: i32,
+	limits: &'a Limits,
+	inboxes: Vec<Sender<puzzle::State>>,
+	/// States sent but not received yet, plus the workers not idle.
+	work: AtomicUsize,
+	/// Cost of the best goal found, `i32::MAX` until one is.
+	incumbent: AtomicI32,
+	stop: AtomicBool,
+	expanded: AtomicUsize,
+	stored: AtomicUsize,
+	aborted: Mutex<Option<puzzle::Limit>>,
+}
+
+impl Shared<'_> {
+	/// Worker owning the states with the cells `cells`.
+	fn owner(&self, cells: &puzzle::Cells) -> usize {
+		let mut hasher = DefaultHasher::new();
+		cells.hash(&mut hasher);
+		return (hasher.finish() % self.inboxes.len() as u64) as usize;
+	}
+
+	/// Whether a state scored `state_score` cannot lead to a cheaper goal than the best one found.
+	fn pruned(&self, state_score: i32) -> bool {
+		let incumbent = self.incumbent.load(Ordering::Relaxed);
+		return incumbent != i32::MAX
+			&& state_score >= (self.score)(incumbent, 0, self.weight_scaled);
+	}
+}
+
+/// States owned by one worker, with the best cost found for each of them.
+struct Worker<'a, 'b> {
+	id: usize,
+	shared: &'b Shared<'a>,
+	states: HashMap<puzzle::Cells, (puzzle::State, i32)>,
+	open_queue: PriorityQueue<puzzle::Cells, Reverse<i32>>,
+	solution: puzzle::Solution,
+	/// States expanded since the last report.
+	unreported: usize,
+	/// States stored at the last report.
+	reported_stored: usize,
+}
+
+impl Worker<'_, '_> {
+	/// Keep `state` if it is the cheapest way found to reach its cells.
+	fn receive(&mut self, state: puzzle::State) {
+		let known = self.states.get(state.packed()).map(|(known, h)| (*known.cost(), *h));
+		if known.is_some_and(|(cost, _)| cost <= *state.cost()) {
+			return;
+		}
+		let h = known.map_or_else(|| (self.shared.distance)(&state, &self.shared.goal), |(_, h)| h);
+		let s_score = (self.shared.score)(*state.cost(), h, self.shared.weight_scaled);
+		if self.shared.pruned(s_score) {
+			return;
+		}
+		let cells = state.packed().clone();
+		self.states.insert(cells.clone(), (state, h));
+		self.open_queue.push(cells, Reverse(s_score));
+	}
+
+	/// Expand the best state of the open queue, sending each neighbor to its owner unless it
+	/// already stopped, the search being aborted.
+	fn expand(&mut self, cells: puzzle::Cells, s_score: i32) {
+		if self.shared.pruned(s_score) {
+			return;
+		}
+		let current_state = self.states[&cells].0.clone();
+		if current_state == self.shared.goal {
+			self.shared.incumbent.fetch_min(*current_state.cost(), Ordering::Relaxed);
+			return;
+		}
+		for mut neighbor in current_state.neighbors() {
+			*(neighbor.cost_mut()) = current_state.cost() + 1;
+			let owner = self.shared.owner(neighbor.packed());
+			if owner == self.id {
+				self.receive(neighbor);
+			} else {
+				self.shared.work.fetch_add(1, Ordering::SeqCst);
+				if self.shared.inboxes[owner].send(neighbor).is_err() {
+					return;
+				}
+			}
+		}
+		self.solution.update_complexity(self.states.len());
+		self.solution.update_frontier(self.open_queue.len());
+		self.unreported += 1;
+		if self.unreported == CHECK_INTERVAL {
+			self.report();
+		}
+	}
+
+	/// Add the states expanded and stored since the last report to the totals of the search,
+	/// stopping every worker when they reach a limit.
+	fn report(&mut self) {
+		let new_stored = self.states.len() - self.reported_stored;
+		let expanded = self.shared.expanded.fetch_add(self.unreported, Ordering::Relaxed);
+		let stored = self.shared.stored.fetch_add(new_stored, Ordering::Relaxed);
+		let limit = self.shared.limits.exceeded(expanded + self.unreported, stored + new_stored);
+		self.unreported = 0;
+		self.reported_stored = self.states.len();
+		if let Some(limit) = limit {
+			self.shared.aborted.lock().unwrap().get_or_insert(limit);
+			self.shared.stop.store(true, Ordering::Relaxed);
+		}
+	}
+
+	/// Expand states until every worker is idle without any state left to receive, returning the
+	/// states met and the complexity of the worker.
+	fn run(
+		mut self,
+		inbox: Receiver<puzzle::State>,
+	) -> (HashMap<puzzle::Cells, puzzle::State>, puzzle::Solution) {
+		let mut busy = true;
+		while !self.shared.stop.load(Ordering::Relaxed) {
+			while let Ok(state) = inbox.try_recv() {
+				if busy {
+					self.shared.work.fetch_sub(1, Ordering::SeqCst);
+				}
+				busy = true;
+				self.receive(state);
+			}
+			if let Some((cells, Reverse(s_score))) = self.open_queue.pop() {
+				self.expand(cells, s_score);
+				continue;
+			}
+			if busy {
+				busy = false;
+				self.shared.work.fetch_sub(1, Ordering::SeqCst);
+			}
+			if self.shared.work.load(Ordering::SeqCst) == 0 {
+				break;
+			}
+			match inbox.recv_timeout(IDLE_WAIT) {
+				Ok(state) => {
+					busy = true;
+					self.receive(state);
+				}
+				Err(RecvTimeoutError::Timeout) => {}
+				Err(RecvTimeoutError::Disconnected) => break,
+			}
+		}
+		if self.shared.stop.load(Ordering::Relaxed) {
+			return (HashMap::new(), self.solution);
+		}
+		let states = self.states.into_iter().map(|(cells, (state, _))| (cells, state)).collect();
+		return (states, self.solution);
+	}
+}
+
+/// Hash distributed A* search from `start` to `goal`, on `threads` worker threads, at least one.
+///
+/// Each state is owned by the worker given by the hash of its cells, which keeps its own open
+/// queue and sends the neighbors it generates to their owner. A state is expanded again when
+/// reached by a cheaper path, and once a goal is found the states whose score cannot beat it are
+/// dropped, so the solution is bounded like the one of [`w_a_star`](super::w_a_star): optimal
+/// with the best first search type, a weight of 1 and a heuristic never overestimating.
+pub fn distributed(
+	mut start: puzzle::State,
+	goal: puzzle::State,
+	distance: Heuristic,
+	score: SearchType,
+	weight: f32,
+	limits: &Limits,
+	threads: usize,
+) -> puzzle::Solution {
+	let threads = threads.max(1);
+	let (inboxes, receivers): (Vec<_>, Vec<_>) = (0..threads).map(|_| mpsc::channel()).unzip();
+	let shared = Shared {
+		goal,
+		distance,
+		score,
+		weight_scaled: (100.0 * weight).round() as i32,
+		limits,
+		inboxes,
+		work: AtomicUsize::new(threads + 1),
+		incumbent: AtomicI32::new(i32::MAX),
+		stop: AtomicBool::new(false),
+		expanded: AtomicUsize::new(0),
+		stored: AtomicUsize::new(0),
+		aborted: Mutex::new(None),
+	};
+	*(start.cost_mut()) = 0;
+	shared.inboxes[shared.owner(start.packed())].send(start).unwrap();
+
+	let mut solution = puzzle::Solution::new();
+	let mut states: HashMap<puzzle::Cells, puzzle::State> = HashMap::new();
+	thread::scope(|scope| {
+		let handles: Vec<_> = receivers
+			.into_iter()
+			.enumerate()
+			.map(|(id, inbox)| {
+				let worker = Worker {
+					id,
+					shared: &shared,
+					states: HashMap::new(),
+					open_queue: PriorityQueue::new(),
+					solution: puzzle::Solution::new(),
+					unreported: 0,
+					reported_stored: 0,
+				};
+				scope.spawn(move || worker.run(inbox))
+			})
+			.collect();
+		for handle in handles {
+			let (worker_states, worker_solution) = handle.join().unwrap();
+			states.extend(worker_states);
+			solution.add_parallel_complexity(&worker_solution);
+		}
+	});
+
+	if let Some(limit) = shared.aborted.into_inner().unwrap() {
+		return solution.abort(limit);
+	}
+	if shared.incumbent.load(Ordering::Relaxed) == i32::MAX {
+		return solution;
+	}
+	let mut path = Vec::new();
+	let mut current_state = states[shared.goal.packed()].clone();
+	while let Some(prev) = current_state.predecessor() {
+		let next = states[&prev].clone();
+		path.push(current_state);
+		current_state = next;
+	}
+	path.push(current_state);
+	path.reverse();
+	return solution.build_from_path(path);
+}
+
+/// Hash distributed A* search on one worker thread per available core, see [`distributed`].
+pub fn hda_star(
+	start: puzzle::State,
+	goal: puzzle::State,
+	distance: Heuristic,
+	score: SearchType,
+	weight: f32,
+	limits: &Limits,
+) -> puzzle::Solution {
+	let threads = thread::available_parallelism().map_or(1, |n| n.get());
+	return distributed(start, goal, distance, score, weight, limits, threads);
+}
@@ -1,18 +1,34 @@
+pub use super::pattern_database::pattern_database;
+pub use super::walking_distance::walking_distance;
 use crate::puzzle;
 
+/// Estimation of the number of moves needed to go from the first state to the second one.
 pub type Heuristic = fn(&puzzle::State, &puzzle::State) -> i32;
 
 impl super::Tool for Heuristic {
 	const DEFAULT: &'static str = "linear_conflict+manhattan";
-	const STR_LIST: [&'static str; 3] = ["hamming", "manhattan", "linear_conflict+manhattan"];
-	const FN_LIST: [Self; 3] = [hamming, manhattan, linear_conflict_manhattan];
+	const STR_LIST: &'static [&'static str] = &[
+		"hamming",
+		"manhattan",
+		"linear_conflict+manhattan",
+		"walking_distance",
+		"pattern_database",
+	];
+	const FN_LIST: &'static [Self] = &[
+		hamming,
+		manhattan,
+		linear_conflict_manhattan,
+		walking_distance,
+		pattern_database,
+	];
 }
 
+/// Sum of the distances of each tile to its target position.
 pub fn manhattan(a: &puzzle::State, b: &puzzle::State) -> i32 {
 	let mut manhattan = 0;
-	let n = a.size() as i32;
-	for x in 0..n {
-		for y in 0..n {
+	let (w, h) = (a.width() as i32, a.height() as i32);
+	for x in 0..w {
+		for y in 0..h {
 			let value = a.access(x, y);
 			if value != 0 {
 				let (x_target, y_target) = b.coord(value);
@@ -25,15 +41,16 @@ pub fn manhattan(a: &puzzle::State, b: &puzzle::State) -> i32 {
 	return manhattan;
 }
 
+/// Number of misplaced tiles.
 pub fn hamming(a: &puzzle::State, b: &puzzle::State) -> i32 {
 	let mut hamming = 0;
-	let n = a.size() as i32;
-	for x in 0..n {
-		for y in 0..n {
+	let (w, h) = (a.width() as i32, a.height() as i32);
+	for x in 0..w {
+		for y in 0..h {
 			let value = a.access(x, y);
 			if value != 0 {
 				let (x_target, y_target) = b.coord(value);
-				if x_target != x && y_target != y {
+				if x_target != x || y_target != y {
 					hamming += 1;
 				}
 			}
@@ -42,65 +59,115 @@ pub fn hamming(a: &puzzle::State, b: &puzzle::State) -> i32 {
 	return hamming;
 }
 
+/// Manhattan distance plus two moves for each tile that has to leave its target line so the
+/// other tiles of this line can reach their target in order.
 pub fn linear_conflict_manhattan(a: &puzzle::State, b: &puzzle::State) -> i32 {
-	let mut manhattan = 0;
-	let mut linear_confict = 0;
-	let n = a.size() as i32;
-	let d = (n * n) as usize;
-	let mut aligned: Vec<(bool, bool)> = Vec::with_capacity(d);
-	aligned.resize(d, (false, false));
-	for x in 0..n {
-		for y in 0..n {
-			let value = a.access(x, y);
-			if value != 0 {
-				let (x_target, y_target) = b.coord(value);
-				let dx = (x_target - x).abs();
-				let dy = (y_target - y).abs();
-				manhattan += dx + dy;
-				aligned[(x + y * n) as usize] = (dx == 0, dy == 0);
-				if dx == 0 {
-					for r in y..n {
-						a.access(x, r);
-					}
-				}
-				if dy == 0 {}
+	let mut linear_conflict = 0;
+	let (w, h) = (a.width() as i32, a.height() as i32);
+	for y in 0..h {
+		let targets: Vec<i32> = (0..w)
+			.map(|x| a.access(x, y))
+			.filter(|value| *value != 0)
+			.map(|value| b.coord(value))
+			.filter(|(_, y_target)| *y_target == y)
+			.map(|(x_target, _)| x_target)
+			.collect();
+		linear_conflict += conflicts(&targets);
+	}
+	for x in 0..w {
+		let targets: Vec<i32> = (0..h)
+			.map(|y| a.access(x, y))
+			.filter(|value| *value != 0)
+			.map(|value| b.coord(value))
+			.filter(|(x_target, _)| *x_target == x)
+			.map(|(_, y_target)| y_target)
+			.collect();
+		linear_conflict += conflicts(&targets);
+	}
+	return manhattan(a, b) + 2 * linear_conflict;
+}
+
+/// Number of tiles to take out of a line so the targets of the others are in increasing order.
+fn conflicts(targets: &[i32]) -> i32 {
+	let mut longest = vec![1; targets.len()];
+	for i in 0..targets.len() {
+		for j in 0..i {
+			if targets[j] < targets[i] {
+				longest[i] = longest[i].max(longest[j] + 1);
 			}
 		}
 	}
-	for x in 0..n {
-		for y in 0..n {
-			let value = a.access(x, y);
-			if value != 0 {
-				let (x_target, y_target) = b.coord(value);
-				let (align_x, align_y) = aligned[(x + y * n) as usize];
-				if align_x {
-					for r in y..n {
-						let other = a.access(x, r);
-						if other != 0 {
-							if let (true, _) = aligned[(x + r * n) as usize] {
-								let (_, y_other_target) = b.coord(other);
-								if y_other_target < y_target {
-									linear_confict += 1;
-								}
-							}
-						}
-					}
-				}
-				if align_y {
-					for c in x..n {
-						let other = a.access(c, y);
-						if other != 0 {
-							if let (_, true) = aligned[(c + y * n) as usize] {
-								let (x_other_target, _) = b.coord(other);
-								if x_other_target < x_target {
-									linear_confict += 1;
-								}
-							}
-						}
-					}
+	return targets.len() as i32 - longest.iter().max().copied().unwrap_or(0);
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::*;
+	use crate::puzzle::{Cells, State};
+	use std::collections::{HashMap, VecDeque};
+
+	/// Exact number of moves from every state able to reach `goal`, found by a breadth first
+	/// search starting from it.
+	pub(crate) fn distances(goal: &State) -> HashMap<Cells, i32> {
+		let mut distances = HashMap::new();
+		let mut queue = VecDeque::new();
+		distances.insert(goal.packed().clone(), 0);
+		queue.push_back(goal.clone());
+		while let Some(state) = queue.pop_front() {
+			let cost = distances[state.packed()];
+			for neighbor in state.neighbors() {
+				if !distances.contains_key(neighbor.packed()) {
+					distances.insert(neighbor.packed().clone(), cost + 1);
+					queue.push_back(neighbor);
 				}
 			}
 		}
+		return distances;
+	}
+
+	/// Check that `heuristic` never overestimates the cost to `goal`, and is 0 on it.
+	pub(crate) fn assert_admissible(heuristic: Heuristic, goal: &State) {
+		assert_eq!(heuristic(goal, goal), 0);
+		for (cells, cost) in distances(goal) {
+			let state =
+				State::new_rectangle(goal.width(), goal.height(), cells.unpack(goal.cell_count()));
+			assert!(heuristic(&state, goal) <= cost, "{:?} overestimated", state.cells());
+		}
+	}
+
+	#[test]
+	fn hamming_is_admissible() {
+		assert_admissible(hamming, &State::goal(3));
+	}
+
+	#[test]
+	fn manhattan_is_admissible() {
+		assert_admissible(manhattan, &State::goal(3));
+	}
+
+	#[test]
+	fn linear_conflict_is_admissible() {
+		assert_admissible(linear_conflict_manhattan, &State::goal(3));
+		assert_admissible(linear_conflict_manhattan, &State::goal_rectangle(2, 4));
+		assert_admissible(linear_conflict_manhattan, &State::goal_rectangle(4, 2));
+	}
+
+	#[test]
+	fn linear_conflict_dominates_manhattan() {
+		let goal = State::goal(3);
+		for cells in distances(&goal).keys() {
+			let state = State::new(3, cells.unpack(9));
+			assert!(linear_conflict_manhattan(&state, &goal) >= manhattan(&state, &goal));
+			assert!(manhattan(&state, &goal) >= hamming(&state, &goal));
+		}
+	}
+
+	#[test]
+	fn known_distances() {
+		let goal = State::goal(3);
+		let state = State::new(3, vec![2, 1, 3, 8, 0, 4, 7, 6, 5]);
+		assert_eq!(hamming(&state, &goal), 2);
+		assert_eq!(manhattan(&state, &goal), 2);
+		assert_eq!(linear_conflict_manhattan(&state, &goal), 4);
 	}
-	return manhattan + 2 * linear_confict;
 }
@@ -0,0 +1,161 @@
+use super::{Heuristic, Limits, SearchType};
+use crate::puzzle;
+
+use std::collections::HashSet;
+
+enum Step {
+	Found,
+	Exceeded(i32),
+	Exhausted,
+	Aborted(puzzle::Limit),
+}
+
+/// Iterative deepening A* search from `start` to `goal`.
+///
+/// Depth first searches are repeated with a growing threshold on the score of the states, so
+/// only the current path is kept in memory. The size complexity is the length of the longest
+/// path explored. Using the best first search type with a weight of 1 gives optimal solutions.
+pub fn ida_star(
+	mut start: puzzle::State,
+	goal: puzzle::State,
+	distance: Heuristic,
+	score: SearchType,
+	weight: f32,
+	limits: &Limits,
+) -> puzzle::Solution {
+	let mut solution = puzzle::Solution::new();
+	let weight_scaled: i32 = (100.0 * weight).round() as i32;
+
+	let mut threshold = score(0, distance(&start, &goal), weight_scaled);
+	*(start.score_mut()) = threshold;
+	let mut on_path: HashSet<puzzle::Cells> = HashSet::new();
+	on_path.insert(start.packed().clone());
+	let mut path = vec![start];
+	loop {
+		let step = search(
+			&mut path,
+			&mut on_path,
+			&goal,
+			distance,
+			score,
+			weight_scaled,
+			threshold,
+			limits,
+			&mut solution,
+		);
+		match step {
+			Step::Found => return solution.build_from_path(path),
+			Step::Exceeded(next_threshold) => threshold = next_threshold,
+			Step::Exhausted => return solution,
+			Step::Aborted(limit) => return solution.abort(limit),
+		}
+	}
+}
+
+#[allow(clippy::too_many_arguments)]
+fn search(
+	path: &mut Vec<puzzle::State>,
+	on_path: &mut HashSet<puzzle::Cells>,
+	goal: &puzzle::State,
+	distance: Heuristic,
+	score: SearchType,
+	weight_scaled: i32,
+	threshold: i32,
+	limits: &Limits,
+	solution: &mut puzzle::Solution,
+) -> Step {
+	let current_state = path.last().unwrap();
+	if current_state == goal {
+		return Step::Found;
+	}
+	let mut next_threshold = None;
+	let neighbors = current_state.neighbors();
+	let cost = current_state.cost() + 1;
+	solution.update_complexity(path.len());
+	solution.update_frontier(path.len());
+	if let Some(limit) = limits.reached(solution) {
+		return Step::Aborted(limit);
+	}
+	for mut neighbor in neighbors {
+		if on_path.contains(neighbor.packed()) {
+			continue;
+		}
+		*(neighbor.cost_mut()) = cost;
+		let n_score = score(cost, distance(&neighbor, goal), weight_scaled);
+		*(neighbor.score_mut()) = n_score;
+		if n_score > threshold {
+			next_threshold = Some(next_threshold.map_or(n_score, |t: i32| t.min(n_score)));
+			continue;
+		}
+		on_path.insert(neighbor.packed().clone());
+		path.push(neighbor);
+		let step = search(
+			path,
+			on_path,
+			goal,
+			distance,
+			score,
+			weight_scaled,
+			threshold,
+			limits,
+			solution,
+		);
+		match step {
+			Step::Found => return Step::Found,
+			Step::Aborted(limit) => return Step::Aborted(limit),
+			Step::Exceeded(t) => {
+				next_threshold = Some(next_threshold.map_or(t, |n: i32| n.min(t)));
+			}
+			Step::Exhausted => {}
+		}
+		let explored = path.pop().unwrap();
+		on_path.remove(explored.packed());
+	}
+	return match next_threshold {
+		Some(t) => Step::Exceeded(t),
+		None => Step::Exhausted,
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::heuristic::{self, tests::distances};
+	use super::super::search_type::best_first;
+	use super::*;
+	use crate::generation;
+
+	use rand::rngs::StdRng;
+	use rand::SeedableRng;
+
+	#[test]
+	fn finds_optimal_solutions() {
+		let goal = puzzle::State::goal(3);
+		let distances = distances(&goal);
+		let mut rng = StdRng::seed_from_u64(42);
+		for _ in 0..10 {
+			let start = generation::uniform(&goal, true, &mut rng);
+			let expected = distances[start.packed()];
+			let solution = ida_star(
+				start,
+				goal.clone(),
+				heuristic::linear_conflict_manhattan,
+				best_first,
+				1.0,
+				&Limits::none(),
+			);
+			assert_eq!(solution.aborted(), None);
+			assert_eq!(solution.directions().unwrap().len() as i32, expected);
+		}
+	}
+
+	#[test]
+	fn stops_at_the_node_limit() {
+		let goal = puzzle::State::goal(4);
+		let mut rng = StdRng::seed_from_u64(42);
+		let start = generation::uniform(&goal, true, &mut rng);
+		let limits = Limits { max_nodes: Some(1000), ..Limits::none() };
+		let solution = ida_star(start, goal, heuristic::manhattan, best_first, 1.0, &limits);
+		assert_eq!(solution.aborted(), Some(puzzle::Limit::Nodes));
+		assert!(solution.moves().is_none());
+	}
+}
@@ -0,0 +1,44 @@
+use crate::puzzle;
+
+use std::mem;
+use std::time::Instant;
+
+/// Bounds on the resources a search may use before giving up.
+///
+/// The memory used is estimated from the highest number of states held at the same time.
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+	pub deadline: Option<Instant>,
+	pub max_nodes: Option<usize>,
+	pub max_memory: Option<usize>,
+}
+
+impl Limits {
+	/// No limit at all, the search runs until it ends.
+	pub fn none() -> Self {
+		Limits::default()
+	}
+
+	/// The first limit exceeded by the search whose complexity is tracked by `solution`.
+	pub fn reached(&self, solution: &puzzle::Solution) -> Option<puzzle::Limit> {
+		return self.exceeded(solution.time_complexity(), solution.size_complexity());
+	}
+
+	/// The first limit exceeded by a search having expanded `nodes` states and holding `size`.
+	pub fn exceeded(&self, nodes: usize, size: usize) -> Option<puzzle::Limit> {
+		if self.max_nodes.is_some_and(|max| nodes >= max) {
+			return Some(puzzle::Limit::Nodes);
+		}
+		if self.max_memory.is_some_and(|max| memory_estimation(size) >= max) {
+			return Some(puzzle::Limit::Memory);
+		}
+		if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+			return Some(puzzle::Limit::Time);
+		}
+		return None;
+	}
+}
+
+fn memory_estimation(size: usize) -> usize {
+	return size * 2 * mem::size_of::<puzzle::State>();
+}
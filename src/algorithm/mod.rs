@@ -1,7 +1,24 @@
-mod heuristic;
+mod ara_star;
+pub use ara_star::{anytime, ara_star};
+mod bidirectional;
+pub use bidirectional::bidirectional;
+mod hda_star;
+pub use hda_star::{distributed, hda_star};
+pub mod heuristic;
 pub use heuristic::Heuristic;
-mod search_type;
+mod limits;
+pub use limits::Limits;
+mod ida_star;
+pub use ida_star::ida_star;
+pub mod pattern_database;
+pub use pattern_database::PatternDatabase;
+mod reduction;
+pub use reduction::reduction;
+pub mod search_type;
 pub use search_type::SearchType;
+mod uninformed;
+pub use uninformed::{breadth_first, depth_first};
+mod walking_distance;
 
 use crate::puzzle;
 
@@ -12,17 +29,50 @@ use std::collections::HashSet;
 
 const DYN_WEIGHTS: [f32; 5] = [1.0, 1.35, 2.5, 4.0, 10.0];
 
+/// Check if `start` can be moved to `goal` by counting inversions between both states.
 pub fn has_solution(start: &puzzle::State, goal: &puzzle::State) -> bool {
-	let inversions = start.count_inversion(&goal);
-	if start.size() % 2 == 0 {
-		let empty_row = start.row_of_empty(&goal);
+	let inversions = start.count_inversion(goal);
+	if start.width().is_multiple_of(2) {
+		let empty_row = start.row_of_empty(goal);
 		return (inversions + empty_row) % 2 == 0;
 	} else {
 		return inversions % 2 == 0;
 	}
 }
 
+/// A search from a start state to a goal state, configured with a heuristic, a search type and
+/// a weight, and stopped early when reaching its limits.
+pub type Algorithm =
+	fn(puzzle::State, puzzle::State, Heuristic, SearchType, f32, &Limits) -> puzzle::Solution;
+
+impl Tool for Algorithm {
+	const DEFAULT: &'static str = "a_star";
+	const STR_LIST: &'static [&'static str] = &[
+		"a_star",
+		"ida_star",
+		"bidirectional",
+		"ara_star",
+		"hda_star",
+		"reduction",
+		"breadth_first",
+		"depth_first",
+	];
+	const FN_LIST: &'static [Self] = &[
+		w_a_star,
+		ida_star,
+		bidirectional,
+		ara_star,
+		hda_star,
+		reduction,
+		breadth_first,
+		depth_first,
+	];
+}
+
+/// Weight used by the best first search type when none is specified, growing with the size,
+/// the largest dimension of the puzzle.
 pub fn dynamic_weight(size: usize) -> f32 {
+	let size = size.max(3);
 	if size > 7 {
 		10.0 * (size - 6) as f32
 	} else {
@@ -30,11 +80,13 @@ pub fn dynamic_weight(size: usize) -> f32 {
 	}
 }
 
-pub trait Tool: Sized + Copy {
+/// A function selectable by name from the command line, like a heuristic or a search type.
+pub trait Tool: Sized + Copy + 'static {
 	const DEFAULT: &'static str;
-	const STR_LIST: [&'static str; 3];
-	const FN_LIST: [Self; 3];
+	const STR_LIST: &'static [&'static str];
+	const FN_LIST: &'static [Self];
 
+	/// Find the function registered under the name `arg`.
 	fn get(arg: &str) -> Option<Self> {
 		if let Some(index) = Self::STR_LIST.iter().position(|txt| txt == &arg) {
 			return Some(Self::FN_LIST[index]);
@@ -42,6 +94,7 @@ pub trait Tool: Sized + Copy {
 		return None;
 	}
 
+	/// Colored and readable version of the name `argname`.
 	fn pretty_name(argname: &str) -> Option<ColoredString> {
 		let lvl = Self::STR_LIST.iter().position(|s| *s == argname)?;
 		let clean_name = argname.replace("_", " ").replace("+", " + ");
@@ -54,12 +107,18 @@ pub trait Tool: Sized + Copy {
 	}
 }
 
+/// Weighted A* search from `start` to `goal`.
+///
+/// `distance` estimates the remaining moves of each state, `score` combines the cost and this
+/// estimation using `weight` to order the open queue. The search is aborted as soon as one of
+/// the `limits` is reached.
 pub fn w_a_star(
 	mut start: puzzle::State,
 	goal: puzzle::State,
 	distance: Heuristic,
 	score: SearchType,
 	weight: f32,
+	limits: &Limits,
 ) -> puzzle::Solution {
 	let mut closed_set: HashSet<puzzle::State> = HashSet::new();
 	let mut open_queue: PriorityQueue<puzzle::State, Reverse<i32>> = PriorityQueue::new();
@@ -70,16 +129,17 @@ pub fn w_a_star(
 	*(start.score_mut()) = s_score;
 	open_queue.push(start, Reverse(s_score));
 	while let Some((current_state, _)) = open_queue.pop() {
-		if current_state.cells() == goal.cells() {
+		if current_state == goal {
 			return solution.build_solution(closed_set, current_state);
 		}
 		for mut neighbor in current_state.neighbors() {
 			*(neighbor.cost_mut()) = current_state.cost() + 1;
 			let n_score = score(*neighbor.cost(), distance(&neighbor, &goal), weight_scaled);
 			*(neighbor.score_mut()) = n_score;
-			if !closed_set.contains(neighbor.cells()) {
+			if !closed_set.contains(neighbor.packed()) {
 				if let Some((state_existing, _)) = open_queue.get(&neighbor) {
 					if state_existing.cost() > neighbor.cost() {
+						open_queue.remove(&neighbor);
 						open_queue.push(neighbor, Reverse(n_score));
 					}
 				} else {
@@ -91,6 +151,10 @@ pub fn w_a_star(
 			return solution;
 		}
 		solution.update_complexity(closed_set.len() + open_queue.len());
+		solution.update_frontier(open_queue.len());
+		if let Some(limit) = limits.reached(&solution) {
+			return solution.abort(limit);
+		}
 	}
 	return solution;
 }
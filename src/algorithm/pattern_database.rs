@@ -0,0 +1,464 @@
+use crate::puzzle;
+
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, RwLock};
+
+const MAGIC: &[u8; 4] = b"NPDB";
+const VERSION: u8 = 3;
+const UNVISITED: u8 = u8::MAX;
+/// Largest number of bytes the costs of all the groups may take.
+const MAX_SIZE: usize = 1 << 30;
+
+static DATABASES: RwLock<Vec<Arc<PatternDatabase>>> = RwLock::new(Vec::new());
+
+/// Additive disjoint pattern database built for one goal.
+///
+/// The tiles are split in groups, each group storing the number of moves of its own tiles needed
+/// to reach the goal from every placement of these tiles, the empty cell being free to reach any
+/// cell the group leaves. As no move is counted twice, the sum of the groups never overestimates
+/// the distance, and a move changes it by at most one so the distance stays consistent.
+#[derive(Debug)]
+pub struct PatternDatabase {
+	goal: puzzle::State,
+	patterns: Vec<Pattern>,
+}
+
+#[derive(Debug)]
+struct Pattern {
+	tiles: Vec<u8>,
+	costs: Vec<u8>,
+}
+
+impl PatternDatabase {
+	/// Partition used when none is specified for a board of `cells` cells: groups of 4 up to 3x3,
+	/// 6-6-3 for 4x4 and groups of 5 above, too large to build above 6x6.
+	pub fn default_partition(cells: usize) -> Vec<usize> {
+		let tiles = cells - 1;
+		let group = match cells {
+			c if c <= 9 => 4,
+			c if c <= 16 => 6,
+			_ => 5,
+		};
+		let mut partition = vec![group; tiles / group];
+		if !tiles.is_multiple_of(group) {
+			partition.push(tiles % group);
+		}
+		return partition;
+	}
+
+	/// Build the database of `goal`, `partition` giving the number of tiles of each group.
+	///
+	/// The groups take the tiles in increasing order, so `[6, 6, 3]` on a 4x4 puzzle makes the
+	/// groups 1 to 6, 7 to 12 and 13 to 15. Each group takes one byte per placement of its tiles,
+	/// about 520MB for a group of 8 tiles on a 4x4 puzzle, and the partitions taking more than 1GB
+	/// are refused before building anything.
+	pub fn build(goal: &puzzle::State, partition: &[usize]) -> Result<Self, Error> {
+		let tile_count = goal.cell_count() - 1;
+		if partition.contains(&0) || partition.iter().sum::<usize>() != tile_count {
+			return Err(Error::InvalidPartition(partition.to_vec(), tile_count));
+		}
+		if !fits(goal.cell_count(), partition) {
+			return Err(Error::TooLarge(partition.to_vec()));
+		}
+		let mut patterns = Vec::with_capacity(partition.len());
+		let mut first_tile = 1;
+		for group in partition {
+			let tiles: Vec<u8> = (first_tile..first_tile + group).map(|t| t as u8).collect();
+			let costs = build_costs(goal, &tiles);
+			patterns.push(Pattern { tiles, costs });
+			first_tile += group;
+		}
+		return Ok(PatternDatabase {
+			goal: goal.clone(),
+			patterns,
+		});
+	}
+
+	/// Load a database previously written by [`PatternDatabase::save`].
+	pub fn load(filename: &str) -> Result<Self, Error> {
+		let mut reader = io::BufReader::new(fs::File::open(filename)?);
+		let mut header = [0; 4];
+		read_exact(&mut reader, &mut header)?;
+		if &header != MAGIC {
+			return Err(Error::BadFormat);
+		}
+		let version = read_u8(&mut reader)?;
+		if version != VERSION {
+			return Err(Error::OldVersion(version));
+		}
+		let width = read_u8(&mut reader)? as usize;
+		let height = read_u8(&mut reader)? as usize;
+		let cells = width * height;
+		if width < 2 || height < 2 || cells > 256 {
+			return Err(Error::BadFormat);
+		}
+		let mut goal = vec![0; cells];
+		read_exact(&mut reader, &mut goal)?;
+		let mut seen = vec![false; cells];
+		for value in goal.iter().map(|value| *value as usize) {
+			if value >= cells || seen[value] {
+				return Err(Error::BadFormat);
+			}
+			seen[value] = true;
+		}
+		let pattern_count = read_u8(&mut reader)? as usize;
+		let mut patterns = Vec::with_capacity(pattern_count);
+		let mut grouped = vec![false; cells];
+		let mut partition = Vec::with_capacity(pattern_count);
+		for _ in 0..pattern_count {
+			let mut tiles = vec![0; read_u8(&mut reader)? as usize];
+			read_exact(&mut reader, &mut tiles)?;
+			for tile in tiles.iter().map(|tile| *tile as usize) {
+				if tile == 0 || tile >= cells || grouped[tile] {
+					return Err(Error::BadFormat);
+				}
+				grouped[tile] = true;
+			}
+			partition.push(tiles.len());
+			if !fits(cells, &partition) {
+				return Err(Error::BadFormat);
+			}
+			let mut costs = vec![0; arrangements(cells, tiles.len()).unwrap()];
+			read_exact(&mut reader, &mut costs)?;
+			patterns.push(Pattern { tiles, costs });
+		}
+		if grouped.iter().skip(1).any(|in_group| !in_group) || reader.read(&mut [0])? != 0 {
+			return Err(Error::BadFormat);
+		}
+		return Ok(PatternDatabase {
+			goal: puzzle::State::new_rectangle(width, height, goal),
+			patterns,
+		});
+	}
+
+	/// Write the database to the file `filename`.
+	pub fn save(&self, filename: &str) -> Result<(), Error> {
+		let mut writer = io::BufWriter::new(fs::File::create(filename)?);
+		writer.write_all(MAGIC)?;
+		writer.write_all(&[VERSION, self.goal.width() as u8, self.goal.height() as u8])?;
+		writer.write_all(&self.goal.cells())?;
+		writer.write_all(&[self.patterns.len() as u8])?;
+		for pattern in self.patterns.iter() {
+			writer.write_all(&[pattern.tiles.len() as u8])?;
+			writer.write_all(&pattern.tiles)?;
+			writer.write_all(&pattern.costs)?;
+		}
+		writer.flush()?;
+		return Ok(());
+	}
+
+	/// Check if the database was built for `goal` with the groups sizes of `partition`.
+	pub fn matches(&self, goal: &puzzle::State, partition: &[usize]) -> bool {
+		return &self.goal == goal
+			&& self.patterns.len() == partition.len()
+			&& self.patterns.iter().zip(partition).all(|(p, n)| p.tiles.len() == *n);
+	}
+
+	/// Sum of the costs of each group for `state`.
+	pub fn distance(&self, state: &puzzle::State) -> i32 {
+		let cells = self.goal.cell_count();
+		let mut positions = vec![0; cells];
+		for (index, value) in state.cells().iter().enumerate() {
+			positions[*value as usize] = index;
+		}
+		let mut distance = 0;
+		for pattern in self.patterns.iter() {
+			let placement: Vec<usize> =
+				pattern.tiles.iter().map(|t| positions[*t as usize]).collect();
+			distance += pattern.costs[rank(&placement, cells)] as i32;
+		}
+		return distance;
+	}
+
+	/// Make the database available to the [`pattern_database`] heuristic.
+	pub fn register(self) {
+		let mut databases = DATABASES.write().unwrap();
+		databases.retain(|db| db.goal != self.goal);
+		databases.push(Arc::new(self));
+	}
+}
+
+/// Distance given by the registered pattern database of the goal `b`.
+///
+/// When no database was registered for this goal, the linear conflict + manhattan distance is
+/// used instead.
+pub fn pattern_database(a: &puzzle::State, b: &puzzle::State) -> i32 {
+	let database = DATABASES
+		.read()
+		.unwrap()
+		.iter()
+		.find(|db| &db.goal == b)
+		.cloned();
+	return match database {
+		Some(db) => db.distance(a),
+		None => super::heuristic::linear_conflict_manhattan(a, b),
+	};
+}
+
+/// Moves of the tiles of the group `tiles` needed to reach `goal` from each of their placements.
+///
+/// A tile can move to any adjacent cell not taken by another tile of the group, as if the empty
+/// cell was always there.
+fn build_costs(goal: &puzzle::State, tiles: &[u8]) -> Vec<u8> {
+	let cells = goal.cell_count();
+	let mut distances = vec![UNVISITED; arrangements(cells, tiles.len()).unwrap()];
+	let mut queue: VecDeque<u32> = VecDeque::new();
+
+	let mut placement: Vec<usize> = tiles.iter().map(|t| index_of(goal, *t)).collect();
+	let start = rank(&placement, cells);
+	distances[start] = 0;
+	queue.push_back(start as u32);
+	while let Some(current) = queue.pop_front() {
+		let distance = distances[current as usize];
+		unrank(current as usize, cells, &mut placement);
+		for tile in 0..placement.len() {
+			let position = placement[tile];
+			for next_position in adjacent_cells(position, goal.width(), goal.height()) {
+				if placement.contains(&next_position) {
+					continue;
+				}
+				placement[tile] = next_position;
+				let next = rank(&placement, cells);
+				placement[tile] = position;
+				if distances[next] == UNVISITED {
+					distances[next] = distance + 1;
+					queue.push_back(next as u32);
+				}
+			}
+		}
+	}
+	return distances;
+}
+
+fn adjacent_cells(index: usize, width: usize, height: usize) -> Vec<usize> {
+	let mut adjacent = Vec::with_capacity(4);
+	let (x, y) = (index % width, index / width);
+	if x > 0 {
+		adjacent.push(index - 1);
+	}
+	if x < width - 1 {
+		adjacent.push(index + 1);
+	}
+	if y > 0 {
+		adjacent.push(index - width);
+	}
+	if y < height - 1 {
+		adjacent.push(index + width);
+	}
+	return adjacent;
+}
+
+fn index_of(state: &puzzle::State, value: u8) -> usize {
+	let (x, y) = state.coord(value);
+	return x as usize + y as usize * state.width();
+}
+
+/// Whether the costs of the groups of `partition` on `cells` cells fit in `MAX_SIZE` bytes.
+fn fits(cells: usize, partition: &[usize]) -> bool {
+	let size = partition
+		.iter()
+		.try_fold(0usize, |size, tiles| size.checked_add(arrangements(cells, *tiles)?));
+	return size.is_some_and(|size| size <= MAX_SIZE);
+}
+
+/// Number of placements of `tiles` tiles on `cells` cells, `None` if there are too many.
+fn arrangements(cells: usize, tiles: usize) -> Option<usize> {
+	if tiles > cells {
+		return None;
+	}
+	return (cells - tiles + 1..=cells).try_fold(1usize, |product, n| product.checked_mul(n));
+}
+
+fn rank(placement: &[usize], cells: usize) -> usize {
+	let mut rank = 0;
+	for (i, position) in placement.iter().enumerate() {
+		let smaller_used = placement[..i].iter().filter(|p| *p < position).count();
+		rank = rank * (cells - i) + position - smaller_used;
+	}
+	return rank;
+}
+
+fn unrank(mut rank: usize, cells: usize, placement: &mut [usize]) {
+	let tiles = placement.len();
+	for i in (0..tiles).rev() {
+		placement[i] = rank % (cells - i);
+		rank /= cells - i;
+	}
+	let mut used: u128 = 0;
+	for digit in placement.iter_mut() {
+		let position = (0..cells)
+			.filter(|p| used & (1 << p) == 0)
+			.nth(*digit)
+			.unwrap();
+		used |= 1 << position;
+		*digit = position;
+	}
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> Result<u8, Error> {
+	let mut byte = [0];
+	read_exact(reader, &mut byte)?;
+	return Ok(byte[0]);
+}
+
+/// Fill `buffer` from `reader`, the file being too short meaning it is not a database.
+fn read_exact<R: Read>(reader: &mut R, buffer: &mut [u8]) -> Result<(), Error> {
+	return reader.read_exact(buffer).map_err(|io_err| match io_err.kind() {
+		io::ErrorKind::UnexpectedEof => Error::BadFormat,
+		_ => Error::FailedFileAccess(io_err),
+	});
+}
+
+/// Errors that can occur while building, loading or saving a pattern database.
+#[derive(Debug)]
+pub enum Error {
+	InvalidPartition(Vec<usize>, usize),
+	TooLarge(Vec<usize>),
+	FailedFileAccess(io::Error),
+	BadFormat,
+	OldVersion(u8),
+}
+
+impl Error {
+	/// Whether the file failing to load can be replaced by a new database: it does not exist or
+	/// was written by another version.
+	pub fn can_rebuild(&self) -> bool {
+		return match self {
+			Error::FailedFileAccess(io_err) => io_err.kind() == io::ErrorKind::NotFound,
+			Error::OldVersion(_) => true,
+			_ => false,
+		};
+	}
+}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		return match self {
+			Error::InvalidPartition(partition, tiles) => write!(
+				f,
+				"Invalid pattern partition {:?}, the groups must cover the {} tiles",
+				partition, tiles
+			),
+			Error::TooLarge(partition) => write!(
+				f,
+				"The pattern partition {:?} would take more than {}MB, use smaller groups",
+				partition,
+				MAX_SIZE >> 20
+			),
+			Error::FailedFileAccess(io_err) => {
+				write!(f, "Could not access the pattern database file: {}", io_err)
+			}
+			Error::BadFormat => write!(f, "The file is not a pattern database"),
+			Error::OldVersion(version) => {
+				write!(f, "The pattern database was written by another version ({})", version)
+			}
+		};
+	}
+}
+
+impl std::convert::From<io::Error> for Error {
+	fn from(io_error: io::Error) -> Error {
+		return Error::FailedFileAccess(io_error);
+	}
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+	use super::super::heuristic::tests::{assert_admissible, distances};
+	use super::*;
+
+	/// Path of a file in the temporary directory, unique to this process and `name`.
+	fn temp_file(name: &str) -> String {
+		let filename = format!("n_puzzle_{}_{}.pdb", std::process::id(), name);
+		return std::env::temp_dir().join(filename).to_string_lossy().into_owned();
+	}
+
+	#[test]
+	fn rank_and_unrank_round_trip() {
+		for tiles in 1..=4 {
+			let mut placement = vec![0; tiles];
+			for expected in 0..arrangements(9, tiles).unwrap() {
+				unrank(expected, 9, &mut placement);
+				assert!(placement.iter().all(|position| *position < 9));
+				assert!((1..tiles).all(|i| !placement[..i].contains(&placement[i])));
+				assert_eq!(rank(&placement, 9), expected);
+			}
+		}
+	}
+
+	#[test]
+	fn save_and_load_round_trip() {
+		let goal = puzzle::State::goal(3);
+		let database = PatternDatabase::build(&goal, &[4, 4]).unwrap();
+		let filename = temp_file("round_trip");
+		database.save(&filename).unwrap();
+		let loaded = PatternDatabase::load(&filename);
+		fs::remove_file(&filename).unwrap();
+		let loaded = loaded.unwrap();
+		assert!(loaded.matches(&goal, &[4, 4]));
+		assert!(!loaded.matches(&goal, &[5, 3]));
+		for cells in distances(&goal).keys() {
+			let state = puzzle::State::new(3, cells.unpack(9));
+			assert_eq!(loaded.distance(&state), database.distance(&state));
+		}
+	}
+
+	#[test]
+	fn admissible_and_consistent() {
+		for goal in [puzzle::State::goal(3), puzzle::State::goal_rectangle(4, 2)] {
+			let partition = PatternDatabase::default_partition(goal.cell_count());
+			let database = PatternDatabase::build(&goal, &partition).unwrap();
+			for cells in distances(&goal).keys() {
+				let state = puzzle::State::new_rectangle(
+					goal.width(),
+					goal.height(),
+					cells.unpack(goal.cell_count()),
+				);
+				for neighbor in state.neighbors() {
+					let step = database.distance(&state) - database.distance(&neighbor);
+					assert!(step.abs() <= 1);
+				}
+			}
+			database.register();
+			assert_admissible(pattern_database, &goal);
+		}
+	}
+
+	#[test]
+	fn load_rejects_other_files() {
+		let missing = PatternDatabase::load(&temp_file("missing")).unwrap_err();
+		assert!(missing.can_rebuild());
+
+		let database = PatternDatabase::build(&puzzle::State::goal(3), &[4, 4]).unwrap();
+		let filename = temp_file("rejected");
+		database.save(&filename).unwrap();
+		let mut bytes = fs::read(&filename).unwrap();
+		bytes.truncate(bytes.len() - 1);
+		fs::write(&filename, &bytes).unwrap();
+		let truncated = PatternDatabase::load(&filename).unwrap_err();
+		bytes[4] = VERSION - 1;
+		fs::write(&filename, &bytes).unwrap();
+		let old = PatternDatabase::load(&filename).unwrap_err();
+		fs::write(&filename, b"not a pattern database").unwrap();
+		let other = PatternDatabase::load(&filename).unwrap_err();
+		fs::remove_file(&filename).unwrap();
+
+		assert!(matches!(truncated, Error::BadFormat) && !truncated.can_rebuild());
+		assert!(matches!(old, Error::OldVersion(_)) && old.can_rebuild());
+		assert!(matches!(other, Error::BadFormat) && !other.can_rebuild());
+	}
+
+	#[test]
+	fn build_rejects_bad_partitions() {
+		let goal = puzzle::State::goal(3);
+		assert!(matches!(PatternDatabase::build(&goal, &[4, 3]), Err(Error::InvalidPartition(..))));
+		assert!(matches!(PatternDatabase::build(&goal, &[8, 0]), Err(Error::InvalidPartition(..))));
+		let large = puzzle::State::goal(6);
+		assert!(matches!(PatternDatabase::build(&large, &[35]), Err(Error::TooLarge(_))));
+		assert!(fits(36, &PatternDatabase::default_partition(36)));
+	}
+}
@@ -0,0 +1,359 @@
+use super::{Heuristic, Limits, SearchType};
+use crate::puzzle::{self, Direction};
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+
+/// Largest dimensions of the region left to the final search.
+const FINAL_SIZE: usize = 3;
+/// Position of the empty cell, then of up to two tracked tiles.
+type Key = [usize; 3];
+/// Position of a tile not tracked.
+const UNTRACKED: usize = usize::MAX;
+
+/// Side of the region whose line of tiles is placed before leaving the region.
+#[derive(Clone, Copy)]
+enum Side {
+	Top,
+	Bottom,
+	Left,
+	Right,
+}
+
+/// Rectangle of the board left to solve, `right` and `bottom` excluded.
+struct Region {
+	left: usize,
+	top: usize,
+	right: usize,
+	bottom: usize,
+}
+
+impl Region {
+	/// Side of the longest dimension whose line has no empty cell in `goal`, until the region
+	/// is small enough for the final search.
+	fn next_side(&self, goal: &puzzle::State) -> Option<Side> {
+		let (width, height) = (self.right - self.left, self.bottom - self.top);
+		let candidates = if height > FINAL_SIZE && (height >= width || width <= FINAL_SIZE) {
+			[Side::Top, Side::Bottom]
+		} else if width > FINAL_SIZE {
+			[Side::Left, Side::Right]
+		} else {
+			return None;
+		};
+		let (x_empty, y_empty) = goal.coord(0);
+		let empty = x_empty as usize + y_empty as usize * goal.width();
+		return candidates
+			.iter()
+			.copied()
+			.find(|side| !self.line(*side, goal.width()).0.contains(&empty));
+	}
+
+	/// Cells of the line on `side`, and the offset going from this line into the region.
+	fn line(&self, side: Side, width: usize) -> (Vec<usize>, isize) {
+		let row = |y: usize| (self.left..self.right).map(|x| x + y * width).collect();
+		let column = |x: usize| (self.top..self.bottom).map(|y| x + y * width).collect();
+		return match side {
+			Side::Top => (row(self.top), width as isize),
+			Side::Bottom => (row(self.bottom - 1), -(width as isize)),
+			Side::Left => (column(self.left), 1),
+			Side::Right => (column(self.right - 1), -1),
+		};
+	}
+
+	fn remove(&mut self, side: Side) {
+		match side {
+			Side::Top => self.top += 1,
+			Side::Bottom => self.bottom -= 1,
+			Side::Left => self.left += 1,
+			Side::Right => self.right -= 1,
+		}
+	}
+}
+
+/// Tiles moved while reducing the puzzle, and the moves done.
+struct Board {
+	cells: Vec<u8>,
+	width: usize,
+	height: usize,
+	empty: usize,
+	moves: Vec<Direction>,
+}
+
+impl Board {
+	/// Cell reached from `index` in `direction`, if it stays on the board.
+	fn step(&self, index: usize, direction: Direction) -> Option<usize> {
+		let (dx, dy) = direction.offset();
+		let x = (index % self.width) as i32 + dx;
+		let y = (index / self.width) as i32 + dy;
+		if x < 0 || x >= self.width as i32 || y < 0 || y >= self.height as i32 {
+			return None;
+		}
+		return Some(x as usize + y as usize * self.width);
+	}
+
+	/// Move the empty cell in `direction`, forgetting the last move when cancelled by this one.
+	fn slide(&mut self, direction: Direction) {
+		let next = self.step(self.empty, direction).unwrap();
+		self.cells.swap(self.empty, next);
+		self.empty = next;
+		if self.moves.last() == Some(&direction.opposite()) {
+			self.moves.pop();
+		} else {
+			self.moves.push(direction);
+		}
+	}
+
+	fn position(&self, tile: u8) -> usize {
+		return self.cells.iter().position(|value| *value == tile).unwrap();
+	}
+
+	/// Shortest moves of the empty cell inside the `allowed` cells leading the `tracked` tiles
+	/// to positions accepted by `done`, applied to the board.
+	///
+	/// Returns whether such moves exist, or the limit reached while searching them.
+	fn shift<F: Fn(&Key) -> bool>(
+		&mut self,
+		allowed: &[bool],
+		tracked: &[u8],
+		done: F,
+		limits: &Limits,
+		solution: &mut puzzle::Solution,
+	) -> Result<bool, puzzle::Limit> {
+		let mut origin = [self.empty, UNTRACKED, UNTRACKED];
+		for (slot, tile) in origin[1..].iter_mut().zip(tracked.iter()) {
+			*slot = self.position(*tile);
+		}
+		let mut parents: HashMap<Key, Option<(Key, Direction)>> = HashMap::new();
+		let mut queue = VecDeque::new();
+		parents.insert(origin, None);
+		queue.push_back(origin);
+		while let Some(key) = queue.pop_front() {
+			if done(&key) {
+				let mut directions = Vec::new();
+				let mut current = key;
+				while let Some((parent, direction)) = parents[&current] {
+					directions.push(direction);
+					current = parent;
+				}
+				for direction in directions.into_iter().rev() {
+					self.slide(direction);
+				}
+				return Ok(true);
+			}
+			for direction in Direction::ALL.iter() {
+				let next_empty = match self.step(key[0], *direction) {
+					Some(next_empty) if allowed[next_empty] => next_empty,
+					_ => continue,
+				};
+				let mut next = key;
+				next[0] = next_empty;
+				for position in next[1..].iter_mut().filter(|position| **position == next_empty) {
+					*position = key[0];
+				}
+				if let Entry::Vacant(entry) = parents.entry(next) {
+					entry.insert(Some((key, *direction)));
+					queue.push_back(next);
+				}
+			}
+			solution.update_complexity(parents.len());
+			solution.update_frontier(queue.len());
+			if let Some(limit) = limits.reached(solution) {
+				return Err(limit);
+			}
+		}
+		return Ok(false);
+	}
+}
+
+/// Place the tiles of the line `cells` of the region, whose inside is `inward` away from it.
+///
+/// Each tile but the last two is moved to its target on its own. The last two are first brought
+/// in the window of the two last cells and the four cells inside of them, then placed together
+/// without leaving this window, falling back to a search of both tiles in the whole region.
+fn place_line(
+	board: &mut Board,
+	locked: &mut [bool],
+	goal: &puzzle::State,
+	(cells, inward): (Vec<usize>, isize),
+	limits: &Limits,
+	solution: &mut puzzle::Solution,
+) -> Result<bool, puzzle::Limit> {
+	let goal_cells = goal.cells();
+	let (singles, last_two) = cells.split_at(cells.len() - 2);
+	for cell in singles {
+		let free: Vec<bool> = locked.iter().map(|lock| !lock).collect();
+		if !board.shift(&free, &[goal_cells[*cell]], |key| key[1] == *cell, limits, solution)? {
+			return Ok(false);
+		}
+		locked[*cell] = true;
+	}
+
+	let (a, b) = (last_two[0], last_two[1]);
+	let (tile_a, tile_b) = (goal_cells[a], goal_cells[b]);
+	let window: Vec<usize> = (0..3)
+		.flat_map(|depth| [a, b].map(|cell| (cell as isize + depth * inward) as usize))
+		.collect();
+	let in_window = |position: usize| window.contains(&position);
+	let mut free: Vec<bool> = locked.iter().map(|lock| !lock).collect();
+	if !board.shift(&free, &[tile_b], |key| in_window(key[1]), limits, solution)? {
+		return Ok(false);
+	}
+	let position_b = board.position(tile_b);
+	free[position_b] = false;
+	let entered = board.shift(&free, &[tile_a], |key| in_window(key[1]), limits, solution)?;
+	free[position_b] = true;
+	let mut placed = false;
+	if entered {
+		let (position_a, position_b) = (board.position(tile_a), board.position(tile_b));
+		free[position_a] = false;
+		free[position_b] = false;
+		let reached = board.shift(&free, &[], |key| in_window(key[0]), limits, solution)?;
+		free[position_a] = true;
+		free[position_b] = true;
+		if reached {
+			let inside: Vec<bool> = (0..free.len()).map(in_window).collect();
+			let targets = |key: &Key| key[1] == a && key[2] == b;
+			placed = board.shift(&inside, &[tile_a, tile_b], targets, limits, solution)?;
+		}
+	}
+	if !placed {
+		let targets = |key: &Key| key[1] == a && key[2] == b;
+		if !board.shift(&free, &[tile_a, tile_b], targets, limits, solution)? {
+			return Ok(false);
+		}
+	}
+	locked[a] = true;
+	locked[b] = true;
+	return Ok(true);
+}
+
+/// Reduction search from `start` to `goal`, placing the puzzle line by line.
+///
+/// The lines of the sides of the longest dimension are placed first, with small searches of one
+/// or two tiles, until the region left is at most 3x3. This region is then solved by weighted A*
+/// with `distance`, `score` and `weight`. The solution is found quickly for any dimension, but
+/// is far from optimal.
+pub fn reduction(
+	mut start: puzzle::State,
+	goal: puzzle::State,
+	distance: Heuristic,
+	score: SearchType,
+	weight: f32,
+	limits: &Limits,
+) -> puzzle::Solution {
+	let mut solution = puzzle::Solution::new();
+	let (width, height) = (start.width(), start.height());
+	let mut board = Board {
+		cells: start.cells(),
+		width,
+		height,
+		empty: start.cells().iter().position(|value| *value == 0).unwrap(),
+		moves: Vec::new(),
+	};
+	let mut locked = vec![false; width * height];
+	let mut region = Region {
+		left: 0,
+		top: 0,
+		right: width,
+		bottom: height,
+	};
+	while let Some(side) = region.next_side(&goal) {
+		let line = region.line(side, width);
+		match place_line(&mut board, &mut locked, &goal, line, limits, &mut solution) {
+			Ok(true) => region.remove(side),
+			Ok(false) => return solution,
+			Err(limit) => return solution.abort(limit),
+		}
+	}
+
+	let cells: Vec<usize> = (region.top..region.bottom)
+		.flat_map(|y| (region.left..region.right).map(move |x| x + y * width))
+		.collect();
+	let goal_cells = goal.cells();
+	let mut labels = [0u8; 256];
+	for (label, cell) in cells.iter().filter(|cell| goal_cells[**cell] != 0).enumerate() {
+		labels[goal_cells[*cell] as usize] = label as u8 + 1;
+	}
+	let relabel = |values: &[u8]| cells.iter().map(|cell| labels[values[*cell] as usize]).collect();
+	let (region_width, region_height) = (region.right - region.left, region.bottom - region.top);
+	let region_state = |values: &[u8]| {
+		return puzzle::State::new_rectangle(region_width, region_height, relabel(values));
+	};
+	let region_start = region_state(&board.cells);
+	let region_goal = region_state(&goal_cells);
+	let end = super::w_a_star(region_start, region_goal, distance, score, weight, limits);
+	solution.add_complexity(&end);
+	if let Some(limit) = end.aborted() {
+		return solution.abort(limit);
+	}
+	match end.directions() {
+		Some(directions) => directions.into_iter().for_each(|direction| board.slide(direction)),
+		None => return solution,
+	}
+
+	*(start.cost_mut()) = 0;
+	let mut path = vec![start];
+	for direction in board.moves {
+		let current_state = path.last().unwrap();
+		let mut next = current_state.neighbor(direction).unwrap();
+		*(next.cost_mut()) = current_state.cost() + 1;
+		path.push(next);
+	}
+	return solution.build_from_path(path);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::heuristic::linear_conflict_manhattan;
+	use super::super::search_type::best_first;
+	use super::*;
+	use crate::algorithm::Tool;
+	use crate::{generation, verification};
+
+	use rand::rngs::StdRng;
+	use rand::SeedableRng;
+
+	fn reduce(start: &puzzle::State, goal: &puzzle::State, limits: &Limits) -> puzzle::Solution {
+		let distance = linear_conflict_manhattan;
+		return reduction(start.clone(), goal.clone(), distance, best_first, 1.0, limits);
+	}
+
+	#[test]
+	fn solutions_verify_on_rectangles() {
+		let mut rng = StdRng::seed_from_u64(42);
+		let dimensions = [(3, 3), (4, 6), (6, 4), (5, 3), (3, 5), (2, 7), (7, 2), (9, 4), (10, 10)];
+		for goal in puzzle::Goal::FN_LIST {
+			for (width, height) in dimensions {
+				let goal = goal(width, height);
+				let start = generation::uniform(&goal, true, &mut rng);
+				let solution = reduce(&start, &goal, &Limits::none());
+				let moves: Vec<verification::Move> = solution
+					.directions()
+					.unwrap()
+					.into_iter()
+					.map(verification::Move::Direction)
+					.collect();
+				let replay = verification::replay(&start, &goal, &moves).unwrap();
+				assert!(replay.solved, "{}x{} not solved", width, height);
+			}
+		}
+	}
+
+	#[test]
+	fn unsolvable_puzzles() {
+		let goal = puzzle::State::goal_rectangle(5, 4);
+		let start = generation::uniform(&goal, false, &mut StdRng::seed_from_u64(42));
+		let solution = reduce(&start, &goal, &Limits::none());
+		assert!(solution.moves().is_none());
+		assert_eq!(solution.aborted(), None);
+	}
+
+	#[test]
+	fn stops_at_the_node_limit() {
+		let goal = puzzle::State::goal(8);
+		let start = generation::uniform(&goal, true, &mut StdRng::seed_from_u64(42));
+		let limits = Limits { max_nodes: Some(10), ..Limits::none() };
+		let solution = reduce(&start, &goal, &limits);
+		assert_eq!(solution.aborted(), Some(puzzle::Limit::Nodes));
+	}
+}
@@ -1,19 +1,23 @@
+/// Score of a state from its cost `g`, its estimated distance `h` and a weight `w` scaled by 100.
 pub type SearchType = fn(i32, i32, i32) -> i32;
 
 impl super::Tool for SearchType {
 	const DEFAULT: &'static str = "best_first";
-	const STR_LIST: [&'static str; 3] = ["uniform_cost", "greedy", "best_first"];
-	const FN_LIST: [Self; 3] = [uniform_cost, greedy, best_first];
+	const STR_LIST: &'static [&'static str] = &["uniform_cost", "greedy", "best_first"];
+	const FN_LIST: &'static [Self] = &[uniform_cost, greedy, best_first];
 }
 
+/// Weighted A* score, `w` is applied to the distance only.
 pub fn best_first(g: i32, h: i32, w: i32) -> i32 {
 	return 100 * g + w * h;
 }
 
+/// Only the cost is considered, the search is then a breadth first one.
 pub fn uniform_cost(g: i32, _h: i32, _w: i32) -> i32 {
 	return g;
 }
 
+/// Only the distance is considered.
 pub fn greedy(_g: i32, h: i32, _w: i32) -> i32 {
 	return h;
 }
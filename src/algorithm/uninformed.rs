@@ -0,0 +1,126 @@
+use super::{Heuristic, Limits, SearchType};
+use crate::puzzle;
+
+use std::collections::{HashSet, VecDeque};
+
+enum Step {
+	Found,
+	Cut,
+	Exhausted,
+	Aborted(puzzle::Limit),
+}
+
+/// Breadth first search from `start` to `goal`, without any heuristic.
+///
+/// States are expanded in the order they are reached, so the first path reaching the goal is
+/// optimal. The heuristic, the search type and the weight are ignored.
+pub fn breadth_first(
+	mut start: puzzle::State,
+	goal: puzzle::State,
+	_distance: Heuristic,
+	_score: SearchType,
+	_weight: f32,
+	limits: &Limits,
+) -> puzzle::Solution {
+	let mut solution = puzzle::Solution::new();
+	if start == goal {
+		return solution.build_from_path(vec![start]);
+	}
+	*(start.cost_mut()) = 0;
+	let mut reached: HashSet<puzzle::State> = HashSet::new();
+	let mut queue: VecDeque<puzzle::State> = VecDeque::new();
+	reached.insert(start.clone());
+	queue.push_back(start);
+	while let Some(current_state) = queue.pop_front() {
+		for mut neighbor in current_state.neighbors() {
+			if reached.contains(neighbor.packed()) {
+				continue;
+			}
+			*(neighbor.cost_mut()) = current_state.cost() + 1;
+			if neighbor == goal {
+				return solution.build_solution(reached, neighbor);
+			}
+			reached.insert(neighbor.clone());
+			queue.push_back(neighbor);
+		}
+		solution.update_complexity(reached.len());
+		solution.update_frontier(queue.len());
+		if let Some(limit) = limits.reached(&solution) {
+			return solution.abort(limit);
+		}
+	}
+	return solution;
+}
+
+/// Iterative deepening depth first search from `start` to `goal`, without any heuristic.
+///
+/// Depth first searches are repeated with a depth limit growing by one move, so the solution is
+/// optimal while only the current path is kept in memory. The heuristic, the search type and the
+/// weight are ignored.
+pub fn depth_first(
+	mut start: puzzle::State,
+	goal: puzzle::State,
+	_distance: Heuristic,
+	_score: SearchType,
+	_weight: f32,
+	limits: &Limits,
+) -> puzzle::Solution {
+	let mut solution = puzzle::Solution::new();
+
+	*(start.cost_mut()) = 0;
+	let mut on_path: HashSet<puzzle::Cells> = HashSet::new();
+	on_path.insert(start.packed().clone());
+	let mut path = vec![start];
+	let mut depth_limit = 0;
+	loop {
+		match search(&mut path, &mut on_path, &goal, depth_limit, limits, &mut solution) {
+			Step::Found => return solution.build_from_path(path),
+			Step::Cut => depth_limit += 1,
+			Step::Exhausted => return solution,
+			Step::Aborted(limit) => return solution.abort(limit),
+		}
+	}
+}
+
+/// Depth first search of the goal from the end of `path`, up to `depth_limit` moves from the start.
+fn search(
+	path: &mut Vec<puzzle::State>,
+	on_path: &mut HashSet<puzzle::Cells>,
+	goal: &puzzle::State,
+	depth_limit: i32,
+	limits: &Limits,
+	solution: &mut puzzle::Solution,
+) -> Step {
+	let current_state = path.last().unwrap();
+	if current_state == goal {
+		return Step::Found;
+	}
+	if *current_state.cost() >= depth_limit {
+		return Step::Cut;
+	}
+	let neighbors = current_state.neighbors();
+	let cost = current_state.cost() + 1;
+	solution.update_complexity(path.len());
+	solution.update_frontier(path.len());
+	if let Some(limit) = limits.reached(solution) {
+		return Step::Aborted(limit);
+	}
+	let mut cut = false;
+	for mut neighbor in neighbors {
+		if on_path.contains(neighbor.packed()) {
+			continue;
+		}
+		*(neighbor.cost_mut()) = cost;
+		on_path.insert(neighbor.packed().clone());
+		path.push(neighbor);
+		match search(path, on_path, goal, depth_limit, limits, solution) {
+			Step::Found => return Step::Found,
+			Step::Aborted(limit) => return Step::Aborted(limit),
+			Step::Cut => cut = true,
+			Step::Exhausted => {}
+		}
+		let explored = path.pop().unwrap();
+		on_path.remove(explored.packed());
+	}
+	return if cut { Step::Cut } else { Step::Exhausted };
+}
@@ -0,0 +1,168 @@
+use crate::puzzle;
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+
+const MAX_SIZE: usize = 4;
+
+static TABLES: RwLock<Vec<Arc<WalkingDistance>>> = RwLock::new(Vec::new());
+
+/// Walking distance tables of one goal.
+///
+/// A table describes a state by counting, for each row, how many of its tiles belong to each
+/// row of the goal. Every table reachable from the goal one is given the number of vertical moves
+/// needed to reach the goal, computed by a breadth first search. The same is done for the columns
+/// and horizontal moves.
+struct WalkingDistance {
+	goal: puzzle::State,
+	rows: Vec<u8>,
+	columns: Vec<u8>,
+	row_distances: HashMap<Vec<u8>, u8>,
+	column_distances: HashMap<Vec<u8>, u8>,
+}
+
+impl WalkingDistance {
+	fn new(goal: &puzzle::State) -> Self {
+		let (width, height) = (goal.width(), goal.height());
+		let mut rows = vec![0; goal.cell_count()];
+		let mut columns = vec![0; goal.cell_count()];
+		for (index, value) in goal.cells().iter().enumerate() {
+			rows[*value as usize] = (index / width) as u8;
+			columns[*value as usize] = (index % width) as u8;
+		}
+		let row_distances = explore(table(goal, height, &rows, |i| i / width), height);
+		let column_distances = explore(table(goal, width, &columns, |i| i % width), width);
+		return WalkingDistance {
+			goal: goal.clone(),
+			rows,
+			columns,
+			row_distances,
+			column_distances,
+		};
+	}
+
+	fn distance(&self, state: &puzzle::State) -> i32 {
+		let (width, height) = (self.goal.width(), self.goal.height());
+		let row_table = table(state, height, &self.rows, |i| i / width);
+		let column_table = table(state, width, &self.columns, |i| i % width);
+		let vertical = self.row_distances.get(&row_table).copied().unwrap_or(0);
+		let horizontal = self.column_distances.get(&column_table).copied().unwrap_or(0);
+		return vertical as i32 + horizontal as i32;
+	}
+}
+
+/// Sum of the vertical and horizontal walking distances from `a` to `b`.
+///
+/// As both stay admissible, the linear conflict + manhattan distance is returned instead when it
+/// is higher.
+///
+/// The tables of a goal are computed the first time it is used. With more than 4 rows or columns
+/// they become too big to be computed quickly, the linear conflict + manhattan distance is used
+/// instead.
+pub fn walking_distance(a: &puzzle::State, b: &puzzle::State) -> i32 {
+	if b.width() > MAX_SIZE || b.height() > MAX_SIZE {
+		return super::heuristic::linear_conflict_manhattan(a, b);
+	}
+	let known = TABLES
+		.read()
+		.unwrap()
+		.iter()
+		.find(|tables| &tables.goal == b)
+		.cloned();
+	let tables = match known {
+		Some(tables) => tables,
+		None => {
+			let tables = Arc::new(WalkingDistance::new(b));
+			TABLES.write().unwrap().push(tables.clone());
+			tables
+		}
+	};
+	return tables.distance(a).max(super::heuristic::linear_conflict_manhattan(a, b));
+}
+
+fn table<F: Fn(usize) -> usize>(
+	state: &puzzle::State,
+	lines: usize,
+	goal_lines: &[u8],
+	line_of: F,
+) -> Vec<u8> {
+	let mut table = vec![0; lines * lines + 1];
+	for (index, value) in state.cells().iter().enumerate() {
+		if *value == 0 {
+			table[lines * lines] = line_of(index) as u8;
+		} else {
+			table[line_of(index) * lines + goal_lines[*value as usize] as usize] += 1;
+		}
+	}
+	return table;
+}
+
+fn explore(goal_table: Vec<u8>, lines: usize) -> HashMap<Vec<u8>, u8> {
+	let mut distances = HashMap::new();
+	let mut queue = VecDeque::new();
+
+	distances.insert(goal_table.clone(), 0);
+	queue.push_back(goal_table);
+	while let Some(current) = queue.pop_front() {
+		let distance = distances[&current];
+		let empty_line = current[lines * lines] as usize;
+		let mut next_lines = Vec::with_capacity(2);
+		if empty_line > 0 {
+			next_lines.push(empty_line - 1);
+		}
+		if empty_line < lines - 1 {
+			next_lines.push(empty_line + 1);
+		}
+		for line in next_lines {
+			for goal_line in 0..lines {
+				if current[line * lines + goal_line] > 0 {
+					let mut next = current.clone();
+					next[line * lines + goal_line] -= 1;
+					next[empty_line * lines + goal_line] += 1;
+					next[lines * lines] = line as u8;
+					if !distances.contains_key(&next) {
+						distances.insert(next.clone(), distance + 1);
+						queue.push_back(next);
+					}
+				}
+			}
+		}
+	}
+	return distances;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::heuristic::linear_conflict_manhattan;
+	use super::super::heuristic::tests::{assert_admissible, distances};
+	use super::*;
+	use crate::generation;
+
+	use rand::rngs::StdRng;
+	use rand::SeedableRng;
+
+	#[test]
+	fn admissible() {
+		assert_admissible(walking_distance, &puzzle::State::goal(3));
+		assert_admissible(walking_distance, &puzzle::State::goal_rectangle(2, 4));
+	}
+
+	#[test]
+	fn tables_alone_are_admissible() {
+		let goal = puzzle::State::goal(3);
+		let tables = WalkingDistance::new(&goal);
+		assert_eq!(tables.distance(&goal), 0);
+		for (cells, cost) in distances(&goal) {
+			let state = puzzle::State::new(3, cells.unpack(9));
+			assert!(tables.distance(&state) <= cost);
+		}
+	}
+
+	#[test]
+	fn falls_back_on_large_boards() {
+		let goal = puzzle::State::goal(5);
+		let state = generation::random(&goal, true, 100, &mut StdRng::seed_from_u64(42));
+		let expected = linear_conflict_manhattan(&state, &goal);
+		assert_eq!(walking_distance(&state, &goal), expected);
+	}
+}
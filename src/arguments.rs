@@ -1,4 +1,4 @@
-use crate::algorithm::Tool;
+use n_puzzle::Tool;
 
 pub fn get<'a>() -> clap::ArgMatches<'a> {
 	clap::App::new("n-puzzle")
@@ -10,28 +10,99 @@ pub fn get<'a>() -> clap::ArgMatches<'a> {
 				.short("f")
 				.long("file")
 				.value_name("FILENAME")
-				.help("The file to read the puzzle from")
+				.help("The file to read the puzzle from, or the file or directory to read the puzzles from in batch mode, - reading the standard input")
 				.number_of_values(1)
 				.multiple(false),
 		)
+		.arg(
+			clap::Arg::with_name("goal")
+				.short("g")
+				.long("goal")
+				.value_name("GOAL")
+				.default_value(n_puzzle::puzzle::Goal::DEFAULT)
+				.number_of_values(1)
+				.multiple(false)
+				.help("The goal of the puzzle, either a layout (snail, row_major, blank_first) or the file to read it from"),
+		)
+		.arg(
+			clap::Arg::with_name("batch")
+				.short("b")
+				.long("batch")
+				.takes_value(false)
+				.requires("file")
+				.help("Solve every puzzle of the file, or of every file of the directory, given with --file"),
+		)
+		.arg(
+			clap::Arg::with_name("play")
+				.short("P")
+				.long("play")
+				.takes_value(false)
+				.conflicts_with_all(&["batch", "verify"])
+				.help("Play the puzzle in the terminal with the arrow keys instead of solving it, u undoing a move and h giving a hint"),
+		)
+		.arg(
+			clap::Arg::with_name("verify")
+				.short("v")
+				.long("verify")
+				.value_name("MOVES")
+				.number_of_values(1)
+				.multiple(false)
+				.conflicts_with("batch")
+				.help("Replay the moves, directions of the empty cell (U, D, L, R) or numbers of the moved tiles, from the puzzle instead of solving it, checking they reach the goal"),
+		)
+		.arg(
+			clap::Arg::with_name("algorithm")
+				.short("a")
+				.long("algorithm")
+				.value_name("ALGORITHM")
+				.possible_values(n_puzzle::algorithm::Algorithm::STR_LIST)
+				.default_value(n_puzzle::algorithm::Algorithm::DEFAULT)
+				.number_of_values(1)
+				.multiple(false)
+				.help("The algorithm used to search the solution, ida_star uses less memory but expands more states, bidirectional searches from both the start and the goal, ara_star keeps lowering the weight to improve its solution until it is optimal or the timeout is reached, hda_star spreads the states over several threads, reduction quickly places the puzzle line by line without looking for a short solution, breadth_first and depth_first find optimal solutions without any heuristic"),
+		)
 		.arg(
 			clap::Arg::with_name("heuristic")
 				.short("h")
 				.long("heuristic")
 				.value_name("HEURISTIC")
-				.possible_values(&crate::algorithm::Heuristic::STR_LIST)
-				.default_value(crate::algorithm::Heuristic::DEFAULT)
+				.possible_values(n_puzzle::algorithm::Heuristic::STR_LIST)
+				.default_value(n_puzzle::algorithm::Heuristic::DEFAULT)
 				.number_of_values(1)
 				.multiple(false)
 				.help("The heuristic used to estimate the distance of each state to the goal"),
 		)
+		.arg(
+			clap::Arg::with_name("partition")
+				.short("p")
+				.long("partition")
+				.value_name("GROUPS")
+				.validator(|raw| {
+					raw.split('-')
+						.map(|n| n.parse::<usize>())
+						.collect::<Result<Vec<usize>, _>>()
+						.map(|_| ())
+						.map_err(|_| String::from("not a list of numbers separated by '-'"))
+				})
+				.number_of_values(1)
+				.multiple(false)
+				.help("The number of tiles of each group of the pattern database heuristic, like 6-6-3. Leave this parameter unset to use a partition depending on the dimension"),
+		)
+		.arg(
+			clap::Arg::with_name("pattern_file")
+				.long("pattern_file")
+				.value_name("FILENAME")
+				.number_of_values(1)
+				.multiple(false)
+				.help("The file the pattern database heuristic is loaded from, it is built and saved there if it does not exist or does not match the puzzle"),
+		)
 		.arg(
 			clap::Arg::with_name("search_type")
 				.short("s")
 				.long("search_type")
 				.value_name("SEARCH_TYPE")
-				.possible_values(&crate::algorithm::SearchType::STR_LIST)
-				.default_value(crate::algorithm::SearchType::DEFAULT)
+				.possible_values(n_puzzle::algorithm::SearchType::STR_LIST)
+				.default_value(n_puzzle::algorithm::SearchType::DEFAULT)
 				.number_of_values(1)
 				.multiple(false)
 				.help("The search type used to set the score of each state"),
@@ -41,14 +112,68 @@ pub fn get<'a>() -> clap::ArgMatches<'a> {
 				.short("w")
 				.long("weight")
 				.value_name("FLOATING_NUMBER")
+				.validator(weight)
+				.number_of_values(1)
+				.multiple(false)
+				.help("The weight applied to the distance when computing the score (best first search type only), the cost has always a weight of 1. Leave this parameter unset to use a dynamic weight"),
+		)
+		.arg(
+			clap::Arg::with_name("output")
+				.short("o")
+				.long("output")
+				.value_name("FORMAT")
+				.possible_values(&["boards", "moves", "tiles", "json", "animation"])
+				.default_value("boards")
+				.number_of_values(1)
+				.multiple(false)
+				.help("The format of the solution: every board, the directions of the empty cell (U, D, L, R), the numbers of the moved tiles, a JSON report or an animation of the boards in the terminal"),
+		)
+		.arg(
+			clap::Arg::with_name("timeout")
+				.short("t")
+				.long("timeout")
+				.value_name("SECONDS")
+				.validator(timeout)
+				.number_of_values(1)
+				.multiple(false)
+				.help("The time after which the search is aborted"),
+		)
+		.arg(
+			clap::Arg::with_name("max_nodes")
+				.short("n")
+				.long("max_nodes")
+				.value_name("NUMBER")
 				.validator(|raw| {
-					raw.parse::<f32>()
+					raw.parse::<usize>()
 						.map(|_| ())
 						.map_err(|_| String::from("not a valid number"))
 				})
 				.number_of_values(1)
 				.multiple(false)
-				.help("The weight applied to the distance when computing the score (best first search type only), the cost has always a weight of 1. Leave this parameter unset to use a dynamic weight"),
+				.help("The number of expanded states after which the search is aborted"),
+		)
+		.arg(
+			clap::Arg::with_name("max_memory")
+				.short("m")
+				.long("max_memory")
+				.value_name("MEGABYTES")
+				.validator(megabytes)
+				.number_of_values(1)
+				.multiple(false)
+				.help("The estimated memory used by the states after which the search is aborted"),
+		)
+		.arg(
+			clap::Arg::with_name("threads")
+				.long("threads")
+				.value_name("NUMBER")
+				.validator(|raw| {
+					raw.parse::<usize>()
+						.map_err(|_| String::from("not a valid number"))
+						.and_then(|n| (n > 0).then_some(()).ok_or(String::from("number below 1")))
+				})
+				.number_of_values(1)
+				.multiple(false)
+				.help("The number of worker threads of the hda_star algorithm (default: one per core)"),
 		)
 		.arg(
 			clap::Arg::with_name("dimension")
@@ -58,13 +183,104 @@ pub fn get<'a>() -> clap::ArgMatches<'a> {
 				.validator(|raw| {
 					raw.parse::<usize>()
 						.map_err(|_| String::from("not a valid number"))
-						.and_then(|n| (n > 2 && n < 11).then(|| ()).ok_or(String::from("number below 3 or above 10")))
+						.and_then(|n| (n > 2 && n < 11).then_some(()).ok_or(String::from("number below 3 or above 10")))
 				})
 				.number_of_values(1)
 				.multiple(false)
 				.conflicts_with("file")
 				.help("The dimension of the puzzle to generate, it will have dimension x dimension cells (int between 3 and 10 both included)"),
 		)
+		.arg(
+			clap::Arg::with_name("rows")
+				.short("r")
+				.long("rows")
+				.value_name("NUMBER")
+				.validator(|raw| {
+					raw.parse::<usize>()
+						.map_err(|_| String::from("not a valid number"))
+						.and_then(|n| (n > 1 && n < 11).then_some(()).ok_or(String::from("number below 2 or above 10")))
+				})
+				.number_of_values(1)
+				.multiple(false)
+				.conflicts_with("file")
+				.help("The number of rows of the puzzle to generate, overriding the dimension (int between 2 and 10 both included)"),
+		)
+		.arg(
+			clap::Arg::with_name("cols")
+				.short("c")
+				.long("cols")
+				.value_name("NUMBER")
+				.validator(|raw| {
+					raw.parse::<usize>()
+						.map_err(|_| String::from("not a valid number"))
+						.and_then(|n| (n > 1 && n < 11).then_some(()).ok_or(String::from("number below 2 or above 10")))
+				})
+				.number_of_values(1)
+				.multiple(false)
+				.conflicts_with("file")
+				.help("The number of columns of the puzzle to generate, overriding the dimension (int between 2 and 10 both included)"),
+		)
+		.arg(
+			clap::Arg::with_name("generator")
+				.long("generator")
+				.value_name("GENERATOR")
+				.possible_values(&["random_walk", "uniform"])
+				.number_of_values(1)
+				.multiple(false)
+				.conflicts_with("file")
+				.help("How the puzzle is generated: random moves from the goal, as many as the iterations, or a shuffle of the tiles giving every puzzle the same chance (default: random_walk)"),
+		)
+		.arg(
+			clap::Arg::with_name("depth")
+				.long("depth")
+				.value_name("MOVES")
+				.validator(|raw| {
+					let bounds = raw
+						.split('-')
+						.map(|n| n.parse::<usize>())
+						.collect::<Result<Vec<usize>, _>>()
+						.map_err(|_| String::from("not a number or two numbers separated by '-'"))?;
+					match bounds[..] {
+						[_] => Ok(()),
+						[min, max] if min <= max => Ok(()),
+						_ => Err(String::from("not a number or two ordered numbers separated by '-'")),
+					}
+				})
+				.number_of_values(1)
+				.multiple(false)
+				.conflicts_with_all(&["file", "without_solution", "generator"])
+				.help("The length of the optimal solution of the puzzle to generate, like 24, or its range, like 20-24, checked with IDA* and the heuristic chosen"),
+		)
+		.arg(
+			clap::Arg::with_name("min_gap")
+				.long("min_gap")
+				.value_name("MOVES")
+				.validator(|raw| raw.parse::<i32>().map(|_| ()).map_err(|_| String::from("not a valid number")))
+				.number_of_values(1)
+				.multiple(false)
+				.requires("depth")
+				.help("The smallest difference between the optimal solution length and the heuristic distance of the puzzle to generate"),
+		)
+		.arg(
+			clap::Arg::with_name("count")
+				.long("count")
+				.value_name("NUMBER")
+				.validator(|raw| raw.parse::<usize>().map(|_| ()).map_err(|_| String::from("not a valid number")))
+				.number_of_values(1)
+				.multiple(false)
+				.conflicts_with_all(&["file", "batch", "play", "verify"])
+				.help("Print this number of generated puzzles in the input format instead of solving one"),
+		)
+		.arg(
+			clap::Arg::with_name("seed")
+				.long("seed")
+				.value_name("NUMBER")
+				.validator(|raw| raw.parse::<u64>().map(|_| ()).map_err(|_| String::from("not a valid number")))
+				.number_of_values(1)
+				.multiple(false)
+				.conflicts_with("file")
+				.help("The seed of the random generation of the puzzle, printed with the puzzle when unset so it can be generated again"),
+		)
 		.arg(
 			clap::Arg::with_name("without_solution")
 				.short("W")
@@ -88,5 +304,163 @@ pub fn get<'a>() -> clap::ArgMatches<'a> {
 				.conflicts_with("file")
 				.help("The number of iterations to do when generating a puzzle"),
 		)
+		.subcommand(bench())
 		.get_matches()
 }
+
+fn bench<'a, 'b>() -> clap::App<'a, 'b> {
+	let number = |raw: String| raw.parse::<usize>().map(|_| ()).map_err(|_| String::from("not a valid number"));
+	clap::SubCommand::with_name("bench")
+		.about("Solve a seeded suite of generated puzzles with every combination of heuristic, search type and weight.")
+		.arg(
+			clap::Arg::with_name("dimension")
+				.short("d")
+				.long("dimension")
+				.value_name("NUMBER")
+				.validator(|raw| {
+					raw.parse::<usize>()
+						.map_err(|_| String::from("not a valid number"))
+						.and_then(|n| (n > 2 && n < 11).then_some(()).ok_or(String::from("number below 3 or above 10")))
+				})
+				.default_value("3")
+				.help("The dimension of the puzzles of the suite (int between 3 and 10 both included)"),
+		)
+		.arg(
+			clap::Arg::with_name("count")
+				.short("c")
+				.long("count")
+				.value_name("NUMBER")
+				.validator(number)
+				.default_value("10")
+				.help("The number of puzzles of the suite"),
+		)
+		.arg(
+			clap::Arg::with_name("seed")
+				.long("seed")
+				.value_name("NUMBER")
+				.validator(|raw| raw.parse::<u64>().map(|_| ()).map_err(|_| String::from("not a valid number")))
+				.default_value("42")
+				.help("The seed of the generation of the suite, uniform among the solvable puzzles"),
+		)
+		.arg(
+			clap::Arg::with_name("algorithm")
+				.short("a")
+				.long("algorithm")
+				.value_name("ALGORITHM")
+				.possible_values(n_puzzle::algorithm::Algorithm::STR_LIST)
+				.default_value(n_puzzle::algorithm::Algorithm::DEFAULT)
+				.help("The algorithm used to solve every puzzle"),
+		)
+		.arg(
+			clap::Arg::with_name("heuristics")
+				.long("heuristics")
+				.value_name("HEURISTICS")
+				.possible_values(n_puzzle::algorithm::Heuristic::STR_LIST)
+				.use_delimiter(true)
+				.multiple(true)
+				.help("The heuristics compared, separated by commas (default: all)"),
+		)
+		.arg(
+			clap::Arg::with_name("search_types")
+				.long("search_types")
+				.value_name("SEARCH_TYPES")
+				.possible_values(n_puzzle::algorithm::SearchType::STR_LIST)
+				.use_delimiter(true)
+				.multiple(true)
+				.help("The search types compared, separated by commas (default: all)"),
+		)
+		.arg(
+			clap::Arg::with_name("weights")
+				.long("weights")
+				.value_name("FLOATING_NUMBERS")
+				.validator(weight)
+				.use_delimiter(true)
+				.multiple(true)
+				.default_value("1,2,5")
+				.help("The weights compared with the best first search type, separated by commas"),
+		)
+		.arg(
+			clap::Arg::with_name("timeout")
+				.short("t")
+				.long("timeout")
+				.value_name("SECONDS")
+				.validator(timeout)
+				.help("The time after which the search of one puzzle is aborted"),
+		)
+		.arg(
+			clap::Arg::with_name("max_nodes")
+				.short("n")
+				.long("max_nodes")
+				.value_name("NUMBER")
+				.validator(number)
+				.default_value("1000000")
+				.help("The number of expanded states after which the search of one puzzle is aborted"),
+		)
+		.arg(
+			clap::Arg::with_name("max_memory")
+				.short("m")
+				.long("max_memory")
+				.value_name("MEGABYTES")
+				.validator(megabytes)
+				.help("The estimated memory used by the states after which the search of one puzzle is aborted"),
+		)
+		.arg(
+			clap::Arg::with_name("threads")
+				.long("threads")
+				.value_name("NUMBER")
+				.validator(|raw| {
+					raw.parse::<usize>()
+						.map_err(|_| String::from("not a valid number"))
+						.and_then(|n| (n > 0).then_some(()).ok_or(String::from("number below 1")))
+				})
+				.help("The number of worker threads of the hda_star algorithm (default: one per core)"),
+		)
+		.arg(
+			clap::Arg::with_name("optimal")
+				.long("optimal")
+				.takes_value(false)
+				.help("Search the optimal solution of every puzzle first, to report the optimality gaps"),
+		)
+		.arg(
+			clap::Arg::with_name("optimal_nodes")
+				.long("optimal_nodes")
+				.value_name("NUMBER")
+				.validator(number)
+				.default_value("2000000")
+				.help("The number of expanded states after which the search of an optimal solution is aborted"),
+		)
+		.arg(
+			clap::Arg::with_name("output")
+				.short("o")
+				.long("output")
+				.value_name("FORMAT")
+				.possible_values(&["table", "csv"])
+				.default_value("table")
+				.help("The format of the results: a table of the averages of each combination, or a CSV line for each search"),
+		)
+}
+
+/// Check that `raw` is a weight, positive and finite.
+fn weight(raw: String) -> Result<(), String> {
+	let weight = raw.parse::<f32>().map_err(|_| String::from("not a valid number"))?;
+	return (weight.is_finite() && weight > 0.0)
+		.then_some(())
+		.ok_or(String::from("not a positive and finite number"));
+}
+
+/// Check that `raw` is a number of megabytes whose bytes can be counted.
+fn megabytes(raw: String) -> Result<(), String> {
+	let megabytes = raw.parse::<usize>().map_err(|_| String::from("not a valid number"))?;
+	return megabytes
+		.checked_mul(1024 * 1024)
+		.map(|_| ())
+		.ok_or(String::from("too large number"));
+}
+
+/// Check that `raw` is a number of seconds a duration can hold.
+fn timeout(raw: String) -> Result<(), String> {
+	let seconds = raw.parse::<f32>().map_err(|_| String::from("not a valid number"))?;
+	return std::time::Duration::try_from_secs_f32(seconds)
+		.map(|_| ())
+		.map_err(|_| String::from("negative, infinite or too large number"));
+}
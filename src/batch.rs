@@ -0,0 +1,136 @@
+use n_puzzle::{algorithm, generation, puzzle};
+
+use colored::*;
+use std::collections::HashSet;
+use std::error;
+use std::fs;
+use std::path;
+use std::time;
+
+type Instance = (String, puzzle::State);
+
+/// Solve every puzzle of the file or directory given with `--file`, printing one line per
+/// puzzle and a summary of the whole batch.
+pub fn run(args: &clap::ArgMatches) -> Result<(), Box<dyn error::Error>> {
+	let (search, heuristic, search_type) = crate::tools(args);
+	let instances = instances(args.value_of("file").unwrap())?;
+	let mut prepared_goals = HashSet::new();
+	let (mut solved, mut unsolvable, mut aborted) = (0, 0, 0);
+	let (mut total_moves, mut total_time_complexity) = (0, 0);
+	let mut total_wall_time = time::Duration::from_secs(0);
+
+	println!(
+		"{}",
+		"name\tsize\tstatus\tmoves\ttime complexity\tsize complexity\twall time".dimmed()
+	);
+	let goal_file = crate::goal_file(args)?;
+	for (name, start) in instances {
+		let goal = crate::goal(args, &goal_file, start.width(), start.height())?;
+		let size = format!("{}x{}", start.height(), start.width());
+		if args.value_of("heuristic") == Some("pattern_database")
+			&& prepared_goals.insert(goal.packed().clone())
+		{
+			crate::setup_pattern_database(args, &goal, crate::quiet(args))?;
+		}
+		if !algorithm::has_solution(&start, &goal) {
+			unsolvable += 1;
+			println!("{}\t{}\t{}\t-\t0\t0\t0", name, size, "unsolvable".red());
+			continue;
+		}
+		let weight = crate::weight(args, &goal);
+		let limits = crate::limits(args)?;
+		let started = time::Instant::now();
+		let solution =
+			crate::solve(args, search, (start, goal), (heuristic, search_type, weight), &limits);
+		let wall_time = started.elapsed();
+		total_wall_time += wall_time;
+		total_time_complexity += solution.time_complexity();
+		let (status, moves) = if let Some(limit) = solution.aborted() {
+			aborted += 1;
+			let moves = solution.moves().as_ref().map(|moves| (moves.len() - 1).to_string());
+			(format!("aborted ({})", limit).purple(), moves.unwrap_or(String::from("-")))
+		} else if let Some(moves) = solution.moves() {
+			solved += 1;
+			total_moves += moves.len() - 1;
+			("solved".green(), (moves.len() - 1).to_string())
+		} else {
+			unsolvable += 1;
+			("unsolvable".red(), String::from("-"))
+		};
+		println!(
+			"{}\t{}\t{}\t{}\t{}\t{}\t{:.3}",
+			name,
+			size,
+			status,
+			moves,
+			solution.time_complexity(),
+			solution.size_complexity(),
+			wall_time.as_secs_f64()
+		);
+	}
+	println!(
+		"\npuzzles:\t\t{}\nsolved:\t\t\t{}\nunsolvable:\t\t{}\naborted:\t\t{}",
+		solved + unsolvable + aborted,
+		solved.to_string().green(),
+		unsolvable.to_string().red(),
+		aborted.to_string().purple()
+	);
+	if solved > 0 {
+		println!(
+			"average moves:\t\t{:.2}",
+			total_moves as f64 / solved as f64
+		);
+	}
+	println!(
+		"\ntotal time complexity:\t{}\ntotal wall time:\t{:.3}s",
+		total_time_complexity,
+		total_wall_time.as_secs_f64()
+	);
+	return Ok(());
+}
+
+fn instances(filename: &str) -> Result<Vec<Instance>, Box<dyn error::Error>> {
+	let input = path::Path::new(filename);
+	let mut files = Vec::new();
+	if filename == generation::STDIN {
+		let puzzles = generation::all_from_file(filename)?;
+		return Ok(named(String::from("stdin"), puzzles));
+	}
+	if input.is_dir() {
+		for entry in fs::read_dir(input)? {
+			let entry_path = entry?.path();
+			if entry_path.is_file() {
+				files.push(entry_path);
+			}
+		}
+		files.sort();
+	} else {
+		files.push(input.to_path_buf());
+	}
+	let mut instances = Vec::new();
+	for file in files {
+		let name = file.to_string_lossy().to_string();
+		let puzzles = generation::all_from_file(&name).map_err(|err| match err {
+			generation::Error::Diagnostics(_) => err.to_string(),
+			_ => format!("{}: {}", name, err),
+		})?;
+		instances.append(&mut named(name, puzzles));
+	}
+	return Ok(instances);
+}
+
+fn named(name: String, puzzles: Vec<puzzle::State>) -> Vec<Instance> {
+	let several = puzzles.len() > 1;
+	return puzzles
+		.into_iter()
+		.enumerate()
+		.map(|(index, start)| {
+			let instance_name = if several {
+				format!("{}#{}", name, index + 1)
+			} else {
+				name.clone()
+			};
+			(instance_name, start)
+		})
+		.collect();
+}
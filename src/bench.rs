@@ -0,0 +1,206 @@
+use n_puzzle::{algorithm, generation, puzzle, Tool};
+
+use rand::SeedableRng;
+use std::error;
+use std::time;
+
+/// Heuristic, search type and weight, the weight only applying to the best first search type.
+type Combination<'a> = (&'a str, &'a str, Option<f32>);
+
+/// Search of one puzzle of the suite with one combination.
+struct Run {
+	status: &'static str,
+	nodes: usize,
+	peak_frontier: usize,
+	length: Option<usize>,
+	gap: Option<usize>,
+	wall_time: time::Duration,
+}
+
+/// Solve a seeded suite of puzzles with every combination asked, printing a table or CSV lines.
+///
+/// With `--optimal`, the optimality gap of a solution is its number of extra moves compared to
+/// the optimal one, found beforehand by IDA* with the linear conflict + manhattan distance and a
+/// weight of 1. This reference search has its own node limit, the puzzles it does not solve within
+/// it or the timeout being left out of the gaps.
+pub fn run(args: &clap::ArgMatches) -> Result<(), Box<dyn error::Error>> {
+	let size = args.value_of("dimension").unwrap().parse()?;
+	let goal = puzzle::State::goal(size);
+	let mut rng = rand::rngs::StdRng::seed_from_u64(args.value_of("seed").unwrap().parse()?);
+	let count: usize = args.value_of("count").unwrap().parse()?;
+	let suite: Vec<puzzle::State> =
+		(0..count).map(|_| generation::uniform(&goal, true, &mut rng)).collect();
+	let algorithm = algorithm::Algorithm::get(args.value_of("algorithm").unwrap()).unwrap();
+	let search = |start, goal, heuristic, search_type, weight, limits: &algorithm::Limits| {
+		let settings = (heuristic, search_type, weight);
+		return crate::solve(args, algorithm, (start, goal), settings, limits);
+	};
+	let heuristics: Vec<&str> = args
+		.values_of("heuristics")
+		.map_or(algorithm::Heuristic::STR_LIST.to_vec(), |values| values.collect());
+	let search_types: Vec<&str> = args
+		.values_of("search_types")
+		.map_or(algorithm::SearchType::STR_LIST.to_vec(), |values| values.collect());
+	let weights = args
+		.values_of("weights")
+		.unwrap()
+		.map(|raw| raw.parse())
+		.collect::<Result<Vec<f32>, _>>()?;
+	let csv = args.value_of("output") == Some("csv");
+	if heuristics.contains(&"pattern_database") {
+		crate::setup_pattern_database(args, &goal, csv)?;
+	}
+
+	let mut combinations: Vec<Combination> = Vec::new();
+	for heuristic in heuristics.iter() {
+		for search_type in search_types.iter() {
+			if *search_type == "best_first" {
+				for weight in weights.iter() {
+					combinations.push((heuristic, search_type, Some(*weight)));
+				}
+			} else {
+				combinations.push((heuristic, search_type, None));
+			}
+		}
+	}
+	let optimal = args.is_present("optimal");
+	let optimal_lengths = suite
+		.iter()
+		.map(|start| {
+			if !optimal {
+				return Ok(None);
+			}
+			let mut limits = crate::limits(args)?;
+			limits.max_nodes = Some(args.value_of("optimal_nodes").unwrap().parse()?);
+			limits.max_memory = None;
+			let reference =
+				(algorithm::Heuristic::DEFAULT, algorithm::SearchType::DEFAULT, Some(1.0));
+			Ok(solve(&limits, algorithm::ida_star, start, &goal, reference, None).length)
+		})
+		.collect::<Result<Vec<Option<usize>>, Box<dyn error::Error>>>()?;
+
+	if csv {
+		println!("puzzle,heuristic,search_type,weight,status,nodes,peak_frontier,length,gap,wall_time");
+	} else {
+		println!(
+			"{:<26} {:<13} {:>6} {:>7} {:>12} {:>14} {:>8} {:>6} {:>10}",
+			"heuristic",
+			"search type",
+			"weight",
+			"solved",
+			"nodes",
+			"peak frontier",
+			"length",
+			"gap",
+			"wall time"
+		);
+	}
+	for combination in combinations {
+		let mut runs = Vec::with_capacity(suite.len());
+		for (start, optimal) in suite.iter().zip(optimal_lengths.iter()) {
+			runs.push(solve(&crate::limits(args)?, search, start, &goal, combination, *optimal));
+		}
+		let (heuristic, search_type, weight) = combination;
+		let weight = weight.map_or(String::from("-"), |w| w.to_string());
+		if csv {
+			for (index, run) in runs.iter().enumerate() {
+				println!(
+					"{},{},{},{},{},{},{},{},{},{:.6}",
+					index + 1,
+					heuristic,
+					search_type,
+					weight,
+					run.status,
+					run.nodes,
+					run.peak_frontier,
+					run.length.map_or(String::new(), |length| length.to_string()),
+					run.gap.map_or(String::new(), |gap| gap.to_string()),
+					run.wall_time.as_secs_f64()
+				);
+			}
+		} else {
+			let solved: Vec<&Run> = runs.iter().filter(|run| run.length.is_some()).collect();
+			let gaps: Vec<usize> = solved.iter().filter_map(|run| run.gap).collect();
+			println!(
+				"{:<26} {:<13} {:>6} {:>7} {:>12} {:>14} {:>8} {:>6} {:>9.3}s",
+				heuristic,
+				search_type,
+				weight,
+				format!("{}/{}", solved.len(), runs.len()),
+				mean(solved.iter().map(|run| run.nodes)),
+				mean(solved.iter().map(|run| run.peak_frontier)),
+				mean(solved.iter().filter_map(|run| run.length)),
+				mean(gaps.into_iter()),
+				runs.iter().map(|run| run.wall_time.as_secs_f64()).sum::<f64>() / runs.len() as f64
+			);
+		}
+	}
+	let missing: Vec<String> = (1..=suite.len())
+		.filter(|index| optimal && optimal_lengths[index - 1].is_none())
+		.map(|index| index.to_string())
+		.collect();
+	if !missing.is_empty() {
+		let note = format!(
+			"optimal length unknown for the puzzles {}, left out of the gaps",
+			missing.join(", ")
+		);
+		if csv {
+			eprintln!("{}", note);
+		} else {
+			println!("{}", note);
+		}
+	}
+	return Ok(());
+}
+
+fn solve<F>(
+	limits: &algorithm::Limits,
+	search: F,
+	start: &puzzle::State,
+	goal: &puzzle::State,
+	(heuristic, search_type, weight): Combination,
+	optimal: Option<usize>,
+) -> Run
+where
+	F: Fn(
+		puzzle::State,
+		puzzle::State,
+		algorithm::Heuristic,
+		algorithm::SearchType,
+		f32,
+		&algorithm::Limits,
+	) -> puzzle::Solution,
+{
+	let started = time::Instant::now();
+	let solution = search(
+		start.clone(),
+		goal.clone(),
+		algorithm::Heuristic::get(heuristic).unwrap(),
+		algorithm::SearchType::get(search_type).unwrap(),
+		weight.unwrap_or(1.0),
+		limits,
+	);
+	let wall_time = started.elapsed();
+	let length = solution.directions().map(|directions| directions.len());
+	return Run {
+		status: match (solution.aborted(), length) {
+			(Some(_), _) => "aborted",
+			(None, Some(_)) => "solved",
+			(None, None) => "unsolved",
+		},
+		nodes: solution.time_complexity(),
+		peak_frontier: solution.peak_frontier(),
+		length,
+		gap: length.zip(optimal).map(|(length, optimal)| length - optimal),
+		wall_time,
+	};
+}
+
+/// Average of `values` with one decimal, or `-` without any value.
+fn mean<I: Iterator<Item = usize>>(values: I) -> String {
+	let (sum, count) = values.fold((0, 0), |(sum, count), value| (sum + value, count + 1));
+	if count == 0 {
+		return String::from("-");
+	}
+	return format!("{:.1}", sum as f64 / count as f64);
+}
@@ -1,5 +1,8 @@
-use crate::puzzle;
+use crate::{algorithm, puzzle};
 
+use colored::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use std::collections::HashSet;
 use std::convert;
 use std::error;
@@ -7,12 +10,24 @@ use std::fmt;
 use std::fs;
 use std::io::{self, BufRead};
 use std::num;
+use std::ops;
 
-pub fn random(size: usize, solvable: bool, iteration: usize) -> puzzle::State {
-	let mut state = puzzle::State::goal(size);
+/// File name standing for the standard input.
+pub const STDIN: &str = "-";
+
+/// Random state made by moving `iteration` tiles picked by `rng` from `goal`.
+///
+/// If `solvable` is false, two tiles are swapped at the end so the goal can't be reached.
+pub fn random<R: Rng + ?Sized>(
+	goal: &puzzle::State,
+	solvable: bool,
+	iteration: usize,
+	rng: &mut R,
+) -> puzzle::State {
+	let mut state = goal.clone();
 
 	for _ in 0..iteration {
-		state.shuffle();
+		state.shuffle(rng);
 	}
 	if !solvable {
 		state.corrupt();
@@ -21,90 +36,341 @@ pub fn random(size: usize, solvable: bool, iteration: usize) -> puzzle::State {
 	return state;
 }
 
-pub fn from_file(filename: &str) -> Result<(usize, puzzle::State), Error> {
-	let file = fs::File::open(filename)?;
-	let reader = io::BufReader::new(file);
-	let mut size = None;
-	let mut cells = Vec::new();
-	let mut unordered_cells = HashSet::new();
+/// State picked by `rng` with the same chance among all the ones of the dimensions of `goal`
+/// that can, or can't if `solvable` is false, reach it.
+///
+/// The cells of `goal` are shuffled, then two tiles are swapped if the result is not on the
+/// requested side.
+pub fn uniform<R: Rng + ?Sized>(
+	goal: &puzzle::State,
+	solvable: bool,
+	rng: &mut R,
+) -> puzzle::State {
+	let mut cells = goal.cells();
+	cells.shuffle(rng);
+	let mut state = puzzle::State::new_rectangle(goal.width(), goal.height(), cells);
+	if algorithm::has_solution(&state, goal) != solvable {
+		state.corrupt();
+	}
+	return state;
+}
 
-	for line in reader.lines() {
-		let line = line?;
-		if size.is_none() {
-			size = read_size(line)?;
-			if size.is_some() {
-				let vec_size = size.ok_or(Error::NoSizeSpecified).and_then(|s| {
-					(s > 2 && s < 11)
-						.then(|| s)
-						.ok_or(Error::SizeNotIncorrect(s))
-				})?;
-				cells = Vec::with_capacity(vec_size * vec_size);
+/// Requirements of a puzzle generated by [`with_depth`].
+pub struct Difficulty {
+	/// Accepted lengths of the optimal solution.
+	pub depths: ops::RangeInclusive<usize>,
+	/// Smallest accepted difference between the optimal length and the estimated distance.
+	pub min_gap: i32,
+	/// Number of candidates tried before giving up.
+	pub attempts: usize,
+}
+
+/// Solvable state whose optimal solution to `goal` has a length accepted by `difficulty`,
+/// returned with this length.
+///
+/// Candidates are random walks from `goal` that never undo their last move, one to two times as
+/// long as the longest depth accepted. They are solved by IDA* with the best first search type and
+/// a weight of 1, whatever the algorithm chosen to solve puzzles, so the length found is optimal as
+/// long as `heuristic` never overestimates the distance. `None` is returned when no candidate is
+/// accepted, or when the time limit is reached.
+pub fn with_depth<R: Rng + ?Sized>(
+	goal: &puzzle::State,
+	difficulty: &Difficulty,
+	heuristic: algorithm::Heuristic,
+	limits: &algorithm::Limits,
+	rng: &mut R,
+) -> Option<(puzzle::State, usize)> {
+	let max_depth = *difficulty.depths.end();
+	let best_first = <algorithm::SearchType as algorithm::Tool>::get("best_first").unwrap();
+
+	for _ in 0..difficulty.attempts {
+		let mut state = goal.clone();
+		for _ in 0..rng.gen_range(max_depth..=2 * max_depth) {
+			let undo = state.moved().map(|direction| direction.opposite());
+			let mut neighbors = state.neighbors();
+			neighbors.retain(|neighbor| neighbor.moved() != undo);
+			state = neighbors.swap_remove(rng.gen_range(0..neighbors.len()));
+		}
+		let candidate = puzzle::State::new_rectangle(state.width(), state.height(), state.cells());
+		let distance = heuristic(&candidate, goal);
+		if distance as usize > max_depth {
+			continue;
+		}
+		let solution =
+			algorithm::ida_star(candidate.clone(), goal.clone(), heuristic, best_first, 1.0, limits);
+		if solution.aborted() == Some(puzzle::Limit::Time) {
+			return None;
+		}
+		if let Some(directions) = solution.directions() {
+			let depth = directions.len();
+			if difficulty.depths.contains(&depth) && depth as i32 - distance >= difficulty.min_gap {
+				return Some((candidate, depth));
 			}
-		} else {
-			cells.append(&mut read_cells(
-				line,
-				&mut unordered_cells,
-				size.ok_or(Error::NoSizeSpecified)?,
-			)?);
 		}
 	}
-	let fixed_size = size.ok_or(Error::NoSizeSpecified)?;
-	if cells.len() != fixed_size * fixed_size {
-		return Err(Error::BadPuzzle);
+	return None;
+}
+
+/// The puzzle `state` in the format read by [`from_reader`].
+pub fn format(state: &puzzle::State) -> String {
+	let mut lines = vec![if state.width() == state.height() {
+		state.width().to_string()
+	} else {
+		format!("{} {}", state.height(), state.width())
+	}];
+	let digits = (state.cell_count() - 1).to_string().len();
+	for row in state.cells().chunks(state.width()) {
+		let values: Vec<String> = row.iter().map(|value| format!("{:>digits$}", value)).collect();
+		lines.push(values.join(" "));
+	}
+	return lines.join("\n");
+}
+
+/// Parse the puzzle in the file `filename`, or in the standard input if it is [`STDIN`], see
+/// [`from_reader`] for the format.
+pub fn from_file(filename: &str) -> Result<puzzle::State, Error> {
+	return single(parse(open(filename)?, source_name(filename))?);
+}
+
+/// Parse a puzzle.
+///
+/// The first line holds the dimension of a square puzzle, or its number of rows and columns,
+/// followed by the cells row by row. Anything after a `#` on a line is a comment.
+pub fn from_reader<R: BufRead>(reader: R) -> Result<puzzle::State, Error> {
+	return single(all_from_reader(reader)?);
+}
+
+/// Parse every puzzle in the file `filename`, or in the standard input if it is [`STDIN`], see
+/// [`all_from_reader`] for the format.
+pub fn all_from_file(filename: &str) -> Result<Vec<puzzle::State>, Error> {
+	return parse(open(filename)?, source_name(filename));
+}
+
+/// Parse a sequence of puzzles.
+///
+/// Each puzzle follows the format of [`from_reader`], the next one starting with its own
+/// dimensions once all the rows of the previous one are read. Blank lines and comments can be
+/// used to separate them.
+///
+/// Every error of the input is reported as a [`Diagnostic`], sorted by line and column. After
+/// invalid dimensions, the lines are skipped until the next blank one.
+pub fn all_from_reader<R: BufRead>(reader: R) -> Result<Vec<puzzle::State>, Error> {
+	return parse(reader, "<input>");
+}
+
+fn open(filename: &str) -> Result<Box<dyn BufRead>, Error> {
+	if filename == STDIN {
+		return Ok(Box::new(io::stdin().lock()));
 	}
-	return Ok((fixed_size, puzzle::State::new(fixed_size, cells)));
+	let file = fs::File::open(filename)?;
+	return Ok(Box::new(io::BufReader::new(file)));
 }
 
-fn read_size(line: String) -> Result<Option<usize>, Error> {
-	let cleared_line = clear_line(&line);
+fn source_name(filename: &str) -> &str {
+	return if filename == STDIN { "<stdin>" } else { filename };
+}
 
-	if cleared_line.len() > 0 {
-		if cleared_line.len() > 1 {
-			return Err(Error::NoSizeSpecified);
+fn single(mut puzzles: Vec<puzzle::State>) -> Result<puzzle::State, Error> {
+	return match puzzles.len() {
+		0 => Err(Error::NoSizeSpecified),
+		1 => Ok(puzzles.remove(0)),
+		_ => Err(Error::BadPuzzle),
+	};
+}
+
+/// A word of a line, with the column it starts at.
+struct Token<'a> {
+	column: usize,
+	text: &'a str,
+}
+
+/// A puzzle whose rows are being read.
+struct Pending {
+	width: usize,
+	height: usize,
+	rows: usize,
+	cells: Vec<u8>,
+	used: HashSet<usize>,
+	valid: bool,
+	header: Diagnostic,
+}
+
+impl Pending {
+	fn read_row(&mut self, tokens: &[Token]) -> Vec<(usize, Error)> {
+		let mut errors = Vec::new();
+		let cell_count = self.width * self.height;
+		if tokens.len() != self.width {
+			let error = Error::SizeNotRespected(self.width, tokens.len());
+			errors.push((tokens.len().min(self.width), error));
+		}
+		for (index, token) in tokens.iter().enumerate() {
+			match token.text.parse::<usize>() {
+				Err(parse_error) => errors.push((index, Error::InvalidNumber(parse_error))),
+				Ok(tile) if tile >= cell_count => {
+					errors.push((index, Error::TileTooBig(tile, cell_count)))
+				}
+				Ok(tile) if !self.used.insert(tile) => {
+					errors.push((index, Error::InvalidTile(tile)))
+				}
+				Ok(tile) => self.cells.push(tile as u8),
+			}
 		}
-		return Ok(Some(cleared_line[0].parse()?));
+		self.rows += 1;
+		self.valid &= errors.is_empty();
+		return errors;
 	}
-	return Ok(None);
 }
 
-fn read_cells(
-	line: String,
-	unordered_cells: &mut HashSet<u8>,
-	size: usize,
-) -> Result<Vec<u8>, Error> {
-	let mut row = Vec::with_capacity(size);
-	let cleared_line = clear_line(&line);
+fn parse<R: BufRead>(reader: R, name: &str) -> Result<Vec<puzzle::State>, Error> {
+	let mut puzzles = Vec::new();
+	let mut diagnostics = Vec::new();
+	let mut pending: Option<Pending> = None;
+	let mut skipping = false;
 
-	let row_size = cleared_line.len();
-	if row_size > 0 {
-		if row_size != size {
-			return Err(Error::SizeNotRespected(size, row_size));
+	for (index, line) in reader.lines().enumerate() {
+		let line = line?;
+		let tokens = clear_line(&line);
+		let locate = |at: usize, error: Error| {
+			let token = tokens.get(at);
+			let end = line.split('#').next().unwrap().trim_end().chars().count() + 1;
+			return Diagnostic {
+				filename: name.to_string(),
+				line: index + 1,
+				column: token.map_or(end, |t| t.column),
+				token: token.map_or(String::new(), |t| t.text.to_string()),
+				source: line.clone(),
+				error,
+			};
+		};
+		if tokens.is_empty() {
+			skipping = false;
+			continue;
+		}
+		if skipping {
+			continue;
 		}
-		for cell in cleared_line {
-			let tile = cell.parse()?;
-			if (tile as usize) >= (size * size) || unordered_cells.contains(&tile) {
-				return Err(Error::InvalidTile(tile));
+		if let Some(current) = &mut pending {
+			for (at, error) in current.read_row(&tokens) {
+				diagnostics.push(locate(at, error));
+			}
+			if current.rows == current.height {
+				let done = pending.take().unwrap();
+				if done.valid {
+					puzzles.push(puzzle::State::new_rectangle(done.width, done.height, done.cells));
+				}
+			}
+		} else {
+			match read_dimensions(&tokens) {
+				Ok((width, height)) => {
+					let mut header = locate(0, Error::BadPuzzle);
+					header.token = line.split('#').next().unwrap().trim().to_string();
+					pending = Some(Pending {
+						width,
+						height,
+						rows: 0,
+						cells: Vec::with_capacity(width * height),
+						used: HashSet::new(),
+						valid: true,
+						header,
+					})
+				}
+				Err((at, error)) => {
+					diagnostics.push(locate(at, error));
+					skipping = true;
+				}
 			}
-			unordered_cells.insert(tile);
-			row.push(tile);
 		}
 	}
-	return Ok(row);
+	if let Some(unfinished) = pending {
+		diagnostics.push(unfinished.header);
+	}
+	if !diagnostics.is_empty() {
+		diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+		return Err(Error::Diagnostics(diagnostics));
+	}
+	return Ok(puzzles);
 }
 
-fn clear_line<'a>(line: &'a String) -> Vec<&'a str> {
-	line.split('#').next().unwrap().split_whitespace().collect()
+fn read_dimensions(tokens: &[Token]) -> Result<(usize, usize), (usize, Error)> {
+	let mut values = Vec::with_capacity(2);
+	for (index, token) in tokens.iter().take(2).enumerate() {
+		values.push(token.text.parse::<usize>().map_err(|err| (index, Error::InvalidNumber(err)))?);
+	}
+
+	return match values[..] {
+		[size] => (size > 2 && size < 11)
+			.then_some((size, size))
+			.ok_or((0, Error::SizeNotIncorrect(size))),
+		[rows, columns] if tokens.len() == 2 => {
+			let invalid = |n: usize| !(2..=10).contains(&n);
+			match (invalid(rows), invalid(columns)) {
+				(false, false) => Ok((columns, rows)),
+				(true, _) => Err((0, Error::DimensionsNotIncorrect(rows, columns))),
+				(false, true) => Err((1, Error::DimensionsNotIncorrect(rows, columns))),
+			}
+		}
+		_ => Err((2, Error::NoSizeSpecified)),
+	};
 }
 
+fn clear_line(line: &str) -> Vec<Token<'_>> {
+	let content = line.split('#').next().unwrap();
+	return content
+		.split_whitespace()
+		.map(|text| {
+			let offset = text.as_ptr() as usize - content.as_ptr() as usize;
+			Token {
+				column: content[..offset].chars().count() + 1,
+				text,
+			}
+		})
+		.collect();
+}
+
+/// A parse error with its position in the input.
+#[derive(Debug)]
+pub struct Diagnostic {
+	pub filename: String,
+	pub line: usize,
+	pub column: usize,
+	pub token: String,
+	pub source: String,
+	pub error: Error,
+}
+
+impl fmt::Display for Diagnostic {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let gutter = " ".repeat(self.line.to_string().len());
+		let indent: String = self
+			.source
+			.chars()
+			.take(self.column - 1)
+			.map(|c| if c == '\t' { '\t' } else { ' ' })
+			.collect();
+		let caret = "^".repeat(self.token.chars().count().max(1));
+		writeln!(f, "{}", self.error)?;
+		writeln!(f, "{}--> {}:{}:{}", gutter, self.filename, self.line, self.column)?;
+		writeln!(f, "{} |", gutter)?;
+		writeln!(f, "{} | {}", self.line, self.source)?;
+		return write!(f, "{} | {}{}", gutter, indent, caret.red());
+	}
+}
+
+/// Errors that can occur while parsing a puzzle.
 #[derive(Debug)]
 pub enum Error {
 	NoSizeSpecified,
 	FailedFileReading(io::Error),
 	SizeNotRespected(usize, usize),
 	SizeNotIncorrect(usize),
+	DimensionsNotIncorrect(usize, usize),
 	InvalidNumber(num::ParseIntError),
-	InvalidTile(u8),
+	InvalidTile(usize),
+	TileTooBig(usize, usize),
 	BadPuzzle,
+	GoalNotMatching(usize, usize),
+	DepthNotReached(usize, usize),
+	Diagnostics(Vec<Diagnostic>),
 }
 
 impl fmt::Display for Error {
@@ -116,6 +382,11 @@ impl fmt::Display for Error {
 				"This puzzle has an incorrect size (got {}, expected between 3 and 10 both included)",
 				size
 			),
+			Error::DimensionsNotIncorrect(rows, columns) => write!(
+				f,
+				"This puzzle has incorrect dimensions (got {} rows and {} columns, expected between 2 and 10 both included)",
+				rows, columns
+			),
 			Error::FailedFileReading(io_err) => write!(f, "Could not read the file: {}", io_err),
 			Error::SizeNotRespected(expected, found) => write!(
 				f,
@@ -125,13 +396,30 @@ impl fmt::Display for Error {
 			Error::InvalidNumber(parse_error) => {
 				write!(f, "Invalid Number could not be parsed: {}", parse_error)
 			}
-			Error::InvalidTile(tile) => {
-				write!(f, "Invalid Tile already used or too big: {}", tile)
-			}
+			Error::InvalidTile(tile) => write!(f, "Invalid Tile already used: {}", tile),
+			Error::TileTooBig(tile, cell_count) => write!(
+				f,
+				"Invalid Tile too big: {}, expected below {}",
+				tile, cell_count
+			),
 			Error::BadPuzzle => write!(
 				f,
 				"An invalid amount of values was specified for this puzzle"
 			),
+			Error::GoalNotMatching(rows, columns) => write!(
+				f,
+				"The goal do not have the dimensions of the puzzle, expected {} rows and {} columns",
+				rows, columns
+			),
+			Error::DepthNotReached(min, max) => write!(
+				f,
+				"No puzzle with an optimal solution of {} to {} moves could be generated",
+				min, max
+			),
+			Error::Diagnostics(diagnostics) => {
+				let rendered: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+				write!(f, "{}", rendered.join("\n\n"))
+			}
 		};
 	}
 }
@@ -149,3 +437,63 @@ impl convert::From<num::ParseIntError> for Error {
 }
 
 impl error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Line, column and token of each diagnostic of `input`.
+	fn positions(input: &str) -> Vec<(usize, usize, String)> {
+		return match all_from_reader(input.as_bytes()) {
+			Err(Error::Diagnostics(diagnostics)) => diagnostics
+				.into_iter()
+				.map(|diagnostic| (diagnostic.line, diagnostic.column, diagnostic.token))
+				.collect(),
+			other => panic!("expected diagnostics, got {:?}", other),
+		};
+	}
+
+	#[test]
+	fn parses_square_and_rectangular_puzzles() {
+		let square = "# snail goals\n3\n1 2 3\n8 0 4 # empty\n7 6 5\n";
+		let input = format!("{}\n3 4\n1 2 3 4\n10 11 0 5\n9 8 7 6\n", square);
+		let puzzles = all_from_reader(input.as_bytes()).unwrap();
+		assert_eq!(puzzles, vec![puzzle::State::goal(3), puzzle::State::goal_rectangle(4, 3)]);
+		assert!(from_reader(input.as_bytes()).is_err());
+	}
+
+	#[test]
+	fn format_round_trip() {
+		for state in [puzzle::State::goal(4), puzzle::State::goal_rectangle(2, 5)] {
+			assert_eq!(from_reader(format(&state).as_bytes()).unwrap(), state);
+		}
+	}
+
+	#[test]
+	fn diagnostics_point_at_the_token() {
+		let input = "3\n1 x 2 3\n8 9\n7 6 7\n\n11\n1 2\n\n3 4 # unfinished\n";
+		let expected = vec![
+			(2, 3, String::from("x")),
+			(2, 7, String::from("3")),
+			(3, 3, String::from("9")),
+			(3, 4, String::new()),
+			(4, 5, String::from("7")),
+			(6, 1, String::from("11")),
+			(9, 1, String::from("3 4")),
+		];
+		assert_eq!(positions(input), expected);
+	}
+
+	#[test]
+	fn diagnostics_are_displayed_with_their_source() {
+		let input = "3\n1 2 300\n8 0 4\n7 6 5\n";
+		let Err(Error::Diagnostics(diagnostics)) = from_reader(input.as_bytes()) else {
+			panic!("expected diagnostics");
+		};
+		assert_eq!(diagnostics.len(), 1);
+		let displayed = diagnostics[0].to_string();
+		assert!(matches!(diagnostics[0].error, Error::TileTooBig(300, 9)));
+		assert!(displayed.contains("--> <input>:2:5"));
+		assert!(displayed.contains("2 | 1 2 300"));
+	}
+}
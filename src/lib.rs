@@ -0,0 +1,29 @@
+//! n-puzzle solver library.
+//!
+//! The crate exposes the puzzle representation ([`State`], [`Solution`]), the search
+//! algorithms and their tools ([`Heuristic`], [`SearchType`]), the puzzle generation and
+//! parsing helpers and the replay of move sequences used by the `n-puzzle` binary.
+//!
+//! ```
+//! use n_puzzle::{algorithm, generation, State, Tool};
+//!
+//! let goal = State::goal(3);
+//! let start = generation::random(&goal, true, 100, &mut rand::thread_rng());
+//! let heuristic = algorithm::Heuristic::get("manhattan").unwrap();
+//! let search_type = algorithm::SearchType::get("best_first").unwrap();
+//! if algorithm::has_solution(&start, &goal) {
+//!     let limits = algorithm::Limits::none();
+//!     let solution = algorithm::w_a_star(start, goal, heuristic, search_type, 1.0, &limits);
+//!     assert!(solution.moves().is_some());
+//! }
+//! ```
+
+#![allow(clippy::needless_return)]
+
+pub mod algorithm;
+pub mod generation;
+pub mod puzzle;
+pub mod verification;
+
+pub use algorithm::{Heuristic, SearchType, Tool};
+pub use puzzle::{Solution, State};
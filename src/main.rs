@@ -1,11 +1,30 @@
-mod algorithm;
+#![allow(clippy::needless_return)]
+
 mod arguments;
-mod generation;
-mod puzzle;
+mod batch;
+mod bench;
+mod play;
+mod playback;
+mod report;
 
-use algorithm::Tool;
 use colored::*;
+use n_puzzle::{algorithm, generation, puzzle, verification, Tool};
+use rand::SeedableRng;
 use std::error;
+use std::io::{self, IsTerminal};
+use std::time;
+
+const DEPTH_ATTEMPTS: usize = 1000;
+
+type Setup = (
+	puzzle::State,
+	puzzle::State,
+	Option<u64>,
+	algorithm::Algorithm,
+	algorithm::Heuristic,
+	algorithm::SearchType,
+	f32,
+);
 
 fn main() {
 	match wrapped_main() {
@@ -16,24 +35,120 @@ fn main() {
 
 fn wrapped_main() -> Result<(), Box<dyn error::Error>> {
 	let args = arguments::get();
+	if let Some(bench_args) = args.subcommand_matches("bench") {
+		return bench::run(bench_args);
+	}
+	if args.is_present("batch") {
+		return batch::run(&args);
+	}
 
-	let (size, start, heuristic, search_type, weight) = setup(&args)?;
-	let goal = puzzle::State::goal(size);
+	if args.is_present("count") {
+		return print_generated(&args);
+	}
+
+	let (start, goal, seed, search, heuristic, search_type, weight) = setup(&args)?;
+	if args.is_present("play") {
+		return play::run(&args, start, goal, heuristic);
+	}
 	let split_line = format!(
 		" {:-^size$} ",
 		"",
-		size = size * if size > 3 { 5 } else { 3 }
+		size = start.width() * if start.cell_count() > 9 { 5 } else { 3 }
 	)
 	.dimmed();
-	if !algorithm::has_solution(&start, &goal) {
-		println!("\n{}\n\npuzzle unsolvable.", split_line);
+	if let Some(raw) = args.value_of("verify") {
+		let moves = verification::parse(raw)?;
+		let replay = verification::replay(&start, &goal, &moves)?;
+		println!("\n{}\n\nlast state:\n{}\n{}\n", split_line, replay.last_state, split_line);
+		if replay.solved {
+			println!("solution verified in {} moves.", replay.move_count);
+		} else {
+			println!("solution incorrect, goal not reached after {} moves.", replay.move_count);
+		}
+		return Ok(());
+	}
+	let mut bound = None;
+	let solution = if algorithm::has_solution(&start, &goal) {
+		let limits = limits(&args)?;
+		let started = time::Instant::now();
+		let solution = if args.value_of("algorithm") == Some("ara_star") {
+			let verbose = !quiet(&args);
+			let publish = |found: &puzzle::Solution, found_bound: f32| {
+				bound = Some(found_bound);
+				if verbose {
+					println!(
+						"solution of {} moves found, at most {:.2} times the optimal, after {:.3}s.",
+						found.directions().unwrap().len(),
+						found_bound,
+						started.elapsed().as_secs_f64()
+					);
+				}
+			};
+			algorithm::anytime(
+				start.clone(),
+				goal.clone(),
+				heuristic,
+				search_type,
+				weight,
+				&limits,
+				publish,
+			)
+		} else {
+			let settings = (heuristic, search_type, weight);
+			solve(&args, search, (start.clone(), goal.clone()), settings, &limits)
+		};
+		Some((solution, started.elapsed()))
+	} else {
+		None
+	};
+	if quiet(&args) {
+		let settings = report::Settings {
+			algorithm: args.value_of("algorithm").unwrap(),
+			heuristic: args.value_of("heuristic").unwrap(),
+			search_type: args.value_of("search_type").unwrap(),
+			weight,
+			seed,
+		};
+		let (solution, wall_time) = match &solution {
+			Some((solution, wall_time)) => (Some(solution), *wall_time),
+			None => (None, time::Duration::from_secs(0)),
+		};
+		println!("{}", report::json(&start, &goal, &settings, solution, bound, wall_time));
 		return Ok(());
 	}
-	let solution = algorithm::w_a_star(start, goal, heuristic, search_type, weight);
+	let solution = match solution {
+		Some((solution, _)) => solution,
+		None => {
+			println!("\n{}\n\npuzzle unsolvable.", split_line);
+			return Ok(());
+		}
+	};
+	if let Some(limit) = solution.aborted() {
+		let kept = if solution.moves().is_some() { ", keeping the last solution found" } else { "" };
+		println!("\n{}\n\nsearch aborted: {} limit reached{}.", split_line, limit, kept);
+	}
 	if let Some(moves) = solution.moves() {
 		println!("\n{}\n\nsolution moves:", split_line);
-		for step in moves.iter().rev() {
-			println!("{}\n\n{}", step, split_line);
+		match args.value_of("output") {
+			Some("moves") => {
+				let directions: Vec<String> =
+					solution.directions().unwrap().iter().map(|d| d.to_string()).collect();
+				println!("{}\n\n{}", directions.join(" "), split_line);
+			}
+			Some("animation") => {
+				let steps: Vec<puzzle::State> = moves.iter().rev().cloned().collect();
+				playback::run(&steps)?;
+			}
+			Some("tiles") => {
+				let tiles: Vec<String> =
+					solution.moved_tiles().unwrap().iter().map(|t| t.to_string()).collect();
+				println!("{}\n\n{}", tiles.join(" "), split_line);
+			}
+			_ => {
+				for step in moves.iter().rev() {
+					println!("{}\n\n{}", step, split_line);
+				}
+			}
 		}
 		if let Some(search_type_arg) = args.value_of("search_type") {
 			if search_type_arg == algorithm::SearchType::DEFAULT {
@@ -48,13 +163,14 @@ fn wrapped_main() -> Result<(), Box<dyn error::Error>> {
 			}
 		}
 		println!(
-			"\nheuristic used:\t\t{}\nsearch type used:\t{}\n\n{}\n\npuzzle solved in {} moves.",
+			"\nalgorithm used:\t\t{}\nheuristic used:\t\t{}\nsearch type used:\t{}\n\n{}\n\npuzzle solved in {} moves.",
+			algorithm::Algorithm::pretty_name(args.value_of("algorithm").unwrap()).unwrap(),
 			algorithm::Heuristic::pretty_name(args.value_of("heuristic").unwrap()).unwrap(),
 			algorithm::SearchType::pretty_name(args.value_of("search_type").unwrap()).unwrap(),
 			split_line,
 			moves.len() - 1
 		);
-	} else {
+	} else if solution.aborted().is_none() {
 		println!("\n{}\n\npuzzle unsolvable.", split_line);
 	}
 	println!(
@@ -65,35 +181,259 @@ fn wrapped_main() -> Result<(), Box<dyn error::Error>> {
 	Ok(())
 }
 
-fn setup(
+fn setup(args: &clap::ArgMatches) -> Result<Setup, Box<dyn error::Error>> {
+	let (search, heuristic, search_type) = tools(args);
+	let goal_file = goal_file(args)?;
+	let (start, goal, seed) = if let Some(start) = input(args)? {
+		if !quiet(args) {
+			println!("puzzle parsed:\n{}", start);
+		}
+		let goal = goal(args, &goal_file, start.width(), start.height())?;
+		prepare_heuristic(args, &goal)?;
+		(start, goal, None)
+	} else {
+		let goal = generation_goal(args, &goal_file)?;
+		prepare_heuristic(args, &goal)?;
+		let seed = seed(args)?;
+		let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+		let (start, depth) = generate(args, &goal, &mut rng)?;
+		if !quiet(args) {
+			let depth = depth.map_or(String::new(), |depth| format!(", optimal depth {}", depth));
+			println!("puzzle generated with seed {}{}:\n{}", seed, depth, start);
+		}
+		(start, goal, Some(seed))
+	};
+	if args.value_of("goal") != Some(puzzle::Goal::DEFAULT) && !quiet(args) {
+		println!("goal:\n{}", goal);
+	}
+	let weight = weight(args, &goal);
+	return Ok((start, goal, seed, search, heuristic, search_type, weight));
+}
+
+/// Print the puzzles asked with --count in the input format instead of solving one.
+fn print_generated(args: &clap::ArgMatches) -> Result<(), Box<dyn error::Error>> {
+	let goal = generation_goal(args, &goal_file(args)?)?;
+	prepare_heuristic(args, &goal)?;
+	let seed = seed(args)?;
+	let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+	println!("# generated with seed {}", seed);
+	for _ in 0..args.value_of("count").unwrap().parse()? {
+		let (start, depth) = generate(args, &goal, &mut rng)?;
+		if let Some(depth) = depth {
+			println!("\n# optimal depth {}", depth);
+		} else {
+			println!();
+		}
+		println!("{}", generation::format(&start));
+	}
+	return Ok(());
+}
+
+fn seed(args: &clap::ArgMatches) -> Result<u64, Box<dyn error::Error>> {
+	return Ok(match args.value_of("seed") {
+		Some(raw) => raw.parse()?,
+		None => rand::random(),
+	});
+}
+
+/// Goal of the puzzle to generate, taking the dimensions of the goal file unless others are given.
+fn generation_goal(
+	args: &clap::ArgMatches,
+	goal_file: &Option<puzzle::State>,
+) -> Result<puzzle::State, Box<dyn error::Error>> {
+	let (default_width, default_height) = match goal_file {
+		Some(goal) => (goal.width().to_string(), goal.height().to_string()),
+		None => (String::from("3"), String::from("3")),
+	};
+	let size = args.value_of("dimension");
+	let width = args.value_of("cols").or(size).unwrap_or(&default_width).parse()?;
+	let height = args.value_of("rows").or(size).unwrap_or(&default_height).parse()?;
+	return Ok(goal(args, goal_file, width, height)?);
+}
+
+/// Puzzle generated from `goal` with the generator chosen, and its optimal depth when one was
+/// requested.
+fn generate(
 	args: &clap::ArgMatches,
-) -> Result<
-	(
-		usize,
-		puzzle::State,
-		algorithm::Heuristic,
-		algorithm::SearchType,
-		f32,
-	),
-	Box<dyn error::Error>,
-> {
+	goal: &puzzle::State,
+	rng: &mut rand::rngs::StdRng,
+) -> Result<(puzzle::State, Option<usize>), Box<dyn error::Error>> {
+	let without_solution = args.is_present("without_solution");
+	if let Some(raw) = args.value_of("depth") {
+		let (_, heuristic, _) = tools(args);
+		let bounds: Vec<usize> = raw.split('-').map(|n| n.parse()).collect::<Result<_, _>>()?;
+		let (min, max) = (bounds[0], *bounds.last().unwrap());
+		let difficulty = generation::Difficulty {
+			depths: min..=max,
+			min_gap: args.value_of("min_gap").unwrap_or("0").parse()?,
+			attempts: DEPTH_ATTEMPTS,
+		};
+		let limits = limits(args)?;
+		return match generation::with_depth(goal, &difficulty, heuristic, &limits, rng) {
+			Some((start, depth)) => Ok((start, Some(depth))),
+			None => Err(generation::Error::DepthNotReached(min, max).into()),
+		};
+	}
+	let start = match args.value_of("generator") {
+		Some("uniform") => generation::uniform(goal, !without_solution, rng),
+		_ => {
+			let iterations = args.value_of("iterations").unwrap_or("1000").parse()?;
+			generation::random(goal, !without_solution, iterations, rng)
+		}
+	};
+	return Ok((start, None));
+}
+
+fn prepare_heuristic(
+	args: &clap::ArgMatches,
+	goal: &puzzle::State,
+) -> Result<(), Box<dyn error::Error>> {
+	if args.value_of("heuristic") == Some("pattern_database") {
+		setup_pattern_database(args, goal, quiet(args))?;
+	}
+	return Ok(());
+}
+
+/// Puzzle read from the file given, or piped to the standard input when none is to be generated.
+fn input(args: &clap::ArgMatches) -> Result<Option<puzzle::State>, generation::Error> {
+	if let Some(filename) = args.value_of("file") {
+		return generation::from_file(filename).map(Some);
+	}
+	let generating = [
+		"dimension",
+		"rows",
+		"cols",
+		"without_solution",
+		"generator",
+		"depth",
+		"min_gap",
+		"iterations",
+		"seed",
+	]
+	.iter()
+	.any(|arg| args.is_present(arg));
+	if generating || io::stdin().is_terminal() {
+		return Ok(None);
+	}
+	let mut puzzles = generation::all_from_file(generation::STDIN)?;
+	return match puzzles.len() {
+		0 => Ok(None),
+		1 => Ok(Some(puzzles.remove(0))),
+		_ => Err(generation::Error::BadPuzzle),
+	};
+}
+
+fn goal_file(args: &clap::ArgMatches) -> Result<Option<puzzle::State>, generation::Error> {
+	let raw = args.value_of("goal").unwrap();
+	if puzzle::Goal::get(raw).is_some() {
+		return Ok(None);
+	}
+	return generation::from_file(raw).map(Some);
+}
+
+fn goal(
+	args: &clap::ArgMatches,
+	goal_file: &Option<puzzle::State>,
+	width: usize,
+	height: usize,
+) -> Result<puzzle::State, generation::Error> {
+	return match goal_file {
+		Some(goal) if goal.width() == width && goal.height() == height => Ok(goal.clone()),
+		Some(_) => Err(generation::Error::GoalNotMatching(height, width)),
+		None => Ok(puzzle::Goal::get(args.value_of("goal").unwrap()).unwrap()(width, height)),
+	};
+}
+
+fn tools(
+	args: &clap::ArgMatches,
+) -> (algorithm::Algorithm, algorithm::Heuristic, algorithm::SearchType) {
+	let search = algorithm::Algorithm::get(args.value_of("algorithm").unwrap()).unwrap();
 	let heuristic = algorithm::Heuristic::get(args.value_of("heuristic").unwrap()).unwrap();
 	let search_type = algorithm::SearchType::get(args.value_of("search_type").unwrap()).unwrap();
-	let (size, start) = if let Some(filename) = args.value_of("file") {
-		let (size, start) = generation::from_file(filename)?;
-		println!("puzzle parsed:\n{}", start);
-		(size, start)
-	} else {
-		let size = args.value_of("dimension").unwrap_or("3").parse()?;
-		let without_solution = args.is_present("without_solution");
-		let iterations = args.value_of("iterations").unwrap_or("1000").parse()?;
-		let start = generation::random(size, !without_solution, iterations);
-		println!("puzzle generated:\n{}", start);
-		(size, start)
-	};
-	let weight = args
+	return (search, heuristic, search_type);
+}
+
+fn weight(args: &clap::ArgMatches, goal: &puzzle::State) -> f32 {
+	return args
 		.value_of("weight")
 		.map(|s| s.parse().unwrap())
-		.unwrap_or(crate::algorithm::dynamic_weight(size));
-	return Ok((size, start, heuristic, search_type, weight));
+		.unwrap_or(algorithm::dynamic_weight(goal.width().max(goal.height())));
+}
+
+/// Whether the output is only meant to be read by other programs.
+fn quiet(args: &clap::ArgMatches) -> bool {
+	return args.value_of("output") == Some("json");
+}
+
+/// Search with `search`, or with the number of threads asked when it is the hda_star algorithm.
+fn solve(
+	args: &clap::ArgMatches,
+	search: algorithm::Algorithm,
+	(start, goal): (puzzle::State, puzzle::State),
+	(heuristic, search_type, weight): (algorithm::Heuristic, algorithm::SearchType, f32),
+	limits: &algorithm::Limits,
+) -> puzzle::Solution {
+	if let (Some("hda_star"), Some(raw)) = (args.value_of("algorithm"), args.value_of("threads")) {
+		let threads = raw.parse().unwrap();
+		return algorithm::distributed(start, goal, heuristic, search_type, weight, limits, threads);
+	}
+	return search(start, goal, heuristic, search_type, weight, limits);
+}
+
+fn limits(args: &clap::ArgMatches) -> Result<algorithm::Limits, Box<dyn error::Error>> {
+	let mut limits = algorithm::Limits::none();
+	if let Some(raw) = args.value_of("timeout") {
+		let timeout = time::Duration::try_from_secs_f32(raw.parse()?)?;
+		let deadline = time::Instant::now().checked_add(timeout);
+		limits.deadline = Some(deadline.ok_or("The timeout is too large")?);
+	}
+	if let Some(raw) = args.value_of("max_nodes") {
+		limits.max_nodes = Some(raw.parse()?);
+	}
+	if let Some(raw) = args.value_of("max_memory") {
+		let max_memory = raw.parse::<usize>()?.checked_mul(1024 * 1024);
+		limits.max_memory = Some(max_memory.ok_or("The memory limit is too large")?);
+	}
+	return Ok(limits);
+}
+
+/// Load the pattern database of `goal`, building and saving it first when the file is missing or
+/// does not match, saying so unless `quiet`.
+fn setup_pattern_database(
+	args: &clap::ArgMatches,
+	goal: &puzzle::State,
+	quiet: bool,
+) -> Result<(), Box<dyn error::Error>> {
+	let partition = match args.value_of("partition") {
+		Some(raw) => raw.split('-').map(|n| n.parse()).collect::<Result<Vec<usize>, _>>()?,
+		None => algorithm::PatternDatabase::default_partition(goal.cell_count()),
+	};
+	let partition_name: Vec<String> = partition.iter().map(|n| n.to_string()).collect();
+	let goal_name = match args.value_of("goal").unwrap_or(puzzle::Goal::DEFAULT) {
+		name if puzzle::Goal::get(name).is_some() => name,
+		_ => "custom",
+	};
+	let default_filename = format!(
+		"pattern_database_{}x{}_{}_{}.pdb",
+		goal.height(),
+		goal.width(),
+		goal_name,
+		partition_name.join("-")
+	);
+	let filename = args.value_of("pattern_file").unwrap_or(&default_filename);
+	let database = match algorithm::PatternDatabase::load(filename) {
+		Ok(database) if database.matches(goal, &partition) => database,
+		Err(load_error) if !load_error.can_rebuild() => return Err(Box::new(load_error)),
+		_ => {
+			if !quiet {
+				let partition_name = partition_name.join("-");
+				println!("building pattern database {} into {}...", partition_name, filename);
+			}
+			let database = algorithm::PatternDatabase::build(goal, &partition)?;
+			database.save(filename)?;
+			database
+		}
+	};
+	database.register();
+	return Ok(());
 }
@@ -0,0 +1,152 @@
+use n_puzzle::{algorithm, puzzle, Tool};
+
+use colored::*;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, execute, queue, style, terminal};
+use std::error;
+use std::io::{self, Write};
+
+/// Number of states expanded by the search of a hint when no limit is given.
+const HINT_NODES: usize = 1_000_000;
+
+/// Let the user solve `start` with the arrow keys, in the alternate screen of the terminal.
+pub fn run(
+	args: &clap::ArgMatches,
+	start: puzzle::State,
+	goal: puzzle::State,
+	heuristic: algorithm::Heuristic,
+) -> Result<(), Box<dyn error::Error>> {
+	return alternate_screen(|| play(args, start, &goal, heuristic));
+}
+
+/// Run `interface` in the alternate screen, with the terminal in raw mode, restoring it after.
+pub fn alternate_screen<F>(interface: F) -> Result<(), Box<dyn error::Error>>
+where
+	F: FnOnce() -> Result<(), Box<dyn error::Error>>,
+{
+	let _screen = AlternateScreen::enter()?;
+	return interface();
+}
+
+/// Terminal switched to raw mode and to the alternate screen, restored when dropped, even when
+/// the interface fails or panics.
+struct AlternateScreen;
+
+impl AlternateScreen {
+	fn enter() -> io::Result<Self> {
+		terminal::enable_raw_mode()?;
+		let screen = AlternateScreen;
+		execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+		return Ok(screen);
+	}
+}
+
+impl Drop for AlternateScreen {
+	fn drop(&mut self) {
+		let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+		let _ = terminal::disable_raw_mode();
+	}
+}
+
+fn play(
+	args: &clap::ArgMatches,
+	start: puzzle::State,
+	goal: &puzzle::State,
+	heuristic: algorithm::Heuristic,
+) -> Result<(), Box<dyn error::Error>> {
+	let heuristic_name =
+		algorithm::Heuristic::pretty_name(args.value_of("heuristic").unwrap()).unwrap();
+	let mut history = vec![start];
+	let mut message = String::new();
+	loop {
+		let state = history.last().unwrap();
+		let status = if state == goal {
+			format!("puzzle solved in {} moves!", history.len() - 1).green().to_string()
+		} else {
+			message.clone()
+		};
+		draw(&[
+			format!("{}", state),
+			String::new(),
+			format!("moves:\t\t{}", history.len() - 1),
+			format!("distance:\t{} ({})", heuristic(state, goal), heuristic_name),
+			String::new(),
+			status,
+			String::new(),
+			String::from("arrows: move a tile   u: undo   h: hint   q: quit").dimmed().to_string(),
+		])?;
+
+		let key = match event::read()? {
+			Event::Key(key) if key.kind == KeyEventKind::Press => key,
+			_ => continue,
+		};
+		message.clear();
+		let direction = match key.code {
+			KeyCode::Up => Some(puzzle::Direction::Down),
+			KeyCode::Down => Some(puzzle::Direction::Up),
+			KeyCode::Left => Some(puzzle::Direction::Right),
+			KeyCode::Right => Some(puzzle::Direction::Left),
+			_ => None,
+		};
+		if let Some(next) = direction.and_then(|direction| state.neighbor(direction)) {
+			history.push(next);
+			continue;
+		}
+		match key.code {
+			KeyCode::Char('u') | KeyCode::Backspace if history.len() > 1 => {
+				history.pop();
+			}
+			KeyCode::Char('h') => message = hint(args, state, goal)?,
+			KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+			KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+			_ => {}
+		}
+	}
+}
+
+/// Arrow key moving a tile so that the empty cell goes in `direction`.
+fn arrow(direction: puzzle::Direction) -> &'static str {
+	return match direction {
+		puzzle::Direction::Up => "↓",
+		puzzle::Direction::Down => "↑",
+		puzzle::Direction::Left => "→",
+		puzzle::Direction::Right => "←",
+	};
+}
+
+/// Next move of an optimal solution from `state`, searched by IDA* with the linear conflict +
+/// manhattan distance, within the limits given or `HINT_NODES` expanded states.
+fn hint(
+	args: &clap::ArgMatches,
+	state: &puzzle::State,
+	goal: &puzzle::State,
+) -> Result<String, Box<dyn error::Error>> {
+	if !algorithm::has_solution(state, goal) {
+		return Ok(String::from("no hint: puzzle unsolvable"));
+	}
+	let mut limits = crate::limits(args)?;
+	limits.max_nodes.get_or_insert(HINT_NODES);
+	let distance = algorithm::heuristic::linear_conflict_manhattan;
+	let best_first = algorithm::SearchType::get("best_first").unwrap();
+	let solution =
+		algorithm::ida_star(state.clone(), goal.clone(), distance, best_first, 1.0, &limits);
+	if let Some(limit) = solution.aborted() {
+		return Ok(format!("no hint: {} limit reached", limit));
+	}
+	return Ok(match (solution.directions(), solution.moved_tiles()) {
+		(Some(directions), Some(tiles)) if !directions.is_empty() => {
+			format!("hint: {} (tile {})", arrow(directions[0]), tiles[0])
+		}
+		_ => String::from("no hint: puzzle already solved"),
+	});
+}
+
+/// Clear the screen and print `lines` from its top.
+pub fn draw(lines: &[String]) -> io::Result<()> {
+	let mut stdout = io::stdout();
+	queue!(stdout, cursor::MoveTo(0, 0), terminal::Clear(terminal::ClearType::All))?;
+	for line in lines.iter().flat_map(|text| text.lines()) {
+		queue!(stdout, style::Print(line), cursor::MoveToNextLine(1))?;
+	}
+	return stdout.flush();
+}
@@ -0,0 +1,74 @@
+use crate::play;
+use n_puzzle::puzzle;
+
+use colored::*;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use std::error;
+use std::time;
+
+const DELAYS_MS: [u64; 7] = [2000, 1000, 500, 250, 125, 60, 30];
+const DEFAULT_SPEED: usize = 2;
+
+/// Animate `steps`, from the start to the goal, in a single board redrawn in place.
+pub fn run(steps: &[puzzle::State]) -> Result<(), Box<dyn error::Error>> {
+	return play::alternate_screen(|| animate(steps));
+}
+
+fn animate(steps: &[puzzle::State]) -> Result<(), Box<dyn error::Error>> {
+	let last = steps.len() - 1;
+	let mut current = 0;
+	let mut speed = DEFAULT_SPEED;
+	let mut paused = false;
+	loop {
+		let state = if current == last {
+			String::from("finished").green().to_string()
+		} else if paused {
+			String::from("paused").yellow().to_string()
+		} else {
+			String::from("playing")
+		};
+		play::draw(&[
+			format!("{}", steps[current]),
+			String::new(),
+			format!("move:\t\t{} / {}", current, last),
+			format!("speed:\t\t{:.1} moves/s", 1000.0 / DELAYS_MS[speed] as f32),
+			format!("playback:\t{}", state),
+			String::new(),
+			String::from("space: pause   left/right: step   up/down: speed   home/end: jump   q: quit")
+				.dimmed()
+				.to_string(),
+		])?;
+
+		let delay = time::Duration::from_millis(DELAYS_MS[speed]);
+		if !paused && current < last && !event::poll(delay)? {
+			current += 1;
+			continue;
+		}
+		let key = match event::read()? {
+			Event::Key(key) if key.kind == KeyEventKind::Press => key,
+			_ => continue,
+		};
+		match key.code {
+			KeyCode::Char(' ') if current == last => {
+				current = 0;
+				paused = false;
+			}
+			KeyCode::Char(' ') => paused = !paused,
+			KeyCode::Right | KeyCode::Char('n') => {
+				paused = true;
+				current = (current + 1).min(last);
+			}
+			KeyCode::Left | KeyCode::Char('p') => {
+				paused = true;
+				current = current.saturating_sub(1);
+			}
+			KeyCode::Up | KeyCode::Char('+') => speed = (speed + 1).min(DELAYS_MS.len() - 1),
+			KeyCode::Down | KeyCode::Char('-') => speed = speed.saturating_sub(1),
+			KeyCode::Home => current = 0,
+			KeyCode::End => current = last,
+			KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+			KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+			_ => {}
+		}
+	}
+}
@@ -0,0 +1,134 @@
+/// Cells of a board packed as tightly as its dimension allows.
+///
+/// Up to 16 cells, each tile takes 4 bits of a `u64`. Up to 25 cells, each tile takes 5 bits of
+/// a `u128`. Bigger boards keep one byte per tile on the heap, allocated for each state: an array
+/// sized for a 10x10 board would make the cells of every state more than three times larger,
+/// while only the searches expanding few states, like the reduction or a high weight, can solve
+/// these boards.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Cells {
+	Small(u64),
+	Medium(u128),
+	Large(Box<[u8]>),
+}
+
+const SMALL_BITS: usize = 4;
+const MEDIUM_BITS: usize = 5;
+
+impl Cells {
+	/// Pack the tiles `values`, listed row by row.
+	pub fn pack(values: &[u8]) -> Self {
+		let mut cells = match values.len() {
+			n if n <= 1 << SMALL_BITS => Cells::Small(0),
+			n if n <= 1 << MEDIUM_BITS && n * MEDIUM_BITS <= 128 => Cells::Medium(0),
+			_ => Cells::Large(values.to_vec().into_boxed_slice()),
+		};
+		if let Cells::Large(_) = cells {
+			return cells;
+		}
+		for (index, value) in values.iter().enumerate() {
+			cells.set(index, *value);
+		}
+		return cells;
+	}
+
+	/// Tile at `index`.
+	pub fn get(&self, index: usize) -> u8 {
+		return match self {
+			Cells::Small(bits) => ((bits >> (index * SMALL_BITS)) & 0xf) as u8,
+			Cells::Medium(bits) => ((bits >> (index * MEDIUM_BITS)) & 0x1f) as u8,
+			Cells::Large(bytes) => bytes[index],
+		};
+	}
+
+	/// Replace the tile at `index` by `value`.
+	pub fn set(&mut self, index: usize, value: u8) {
+		match self {
+			Cells::Small(bits) => {
+				let shift = index * SMALL_BITS;
+				*bits = (*bits & !(0xf << shift)) | ((value as u64) << shift);
+			}
+			Cells::Medium(bits) => {
+				let shift = index * MEDIUM_BITS;
+				*bits = (*bits & !(0x1f << shift)) | ((value as u128) << shift);
+			}
+			Cells::Large(bytes) => bytes[index] = value,
+		}
+	}
+
+	/// Exchange the tiles at `a` and `b`.
+	pub fn swap(&mut self, a: usize, b: usize) {
+		let tile_a = self.get(a);
+		let tile_b = self.get(b);
+		self.set(a, tile_b);
+		self.set(b, tile_a);
+	}
+
+	/// Index of the tile `value` among the `len` first cells.
+	pub fn position(&self, value: u8, len: usize) -> Option<usize> {
+		return (0..len).find(|index| self.get(*index) == value);
+	}
+
+	/// The `len` first tiles, one byte each.
+	pub fn unpack(&self, len: usize) -> Vec<u8> {
+		return (0..len).map(|index| self.get(index)).collect();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Cells;
+
+	/// Tiles of a board of `len` cells, in a scrambled but reproducible order.
+	fn scrambled(len: usize) -> Vec<u8> {
+		return (0..len).map(|index| ((index * 7 + 3) % len) as u8).collect();
+	}
+
+	#[test]
+	fn packs_each_size_in_its_variant() {
+		assert!(matches!(Cells::pack(&scrambled(9)), Cells::Small(_)));
+		assert!(matches!(Cells::pack(&scrambled(16)), Cells::Small(_)));
+		assert!(matches!(Cells::pack(&scrambled(25)), Cells::Medium(_)));
+		assert!(matches!(Cells::pack(&scrambled(36)), Cells::Large(_)));
+		assert!(matches!(Cells::pack(&scrambled(100)), Cells::Large(_)));
+	}
+
+	#[test]
+	fn pack_and_unpack_round_trip() {
+		for len in [4, 9, 16, 20, 25, 36, 100] {
+			let values = scrambled(len);
+			let cells = Cells::pack(&values);
+			assert_eq!(cells.unpack(len), values);
+			for (index, value) in values.iter().enumerate() {
+				assert_eq!(cells.get(index), *value);
+				assert_eq!(cells.position(*value, len), Some(index));
+			}
+		}
+	}
+
+	#[test]
+	fn set_only_changes_its_cell() {
+		for len in [9, 16, 25, 36, 100] {
+			let mut values = scrambled(len);
+			let mut cells = Cells::pack(&values);
+			for index in 0..len {
+				let value = (len - 1 - index) as u8;
+				cells.set(index, value);
+				values[index] = value;
+				assert_eq!(cells.unpack(len), values);
+			}
+			assert_eq!(cells, Cells::pack(&values));
+		}
+	}
+
+	#[test]
+	fn swap_exchanges_two_cells() {
+		for len in [9, 16, 25, 100] {
+			let mut values = scrambled(len);
+			let mut cells = Cells::pack(&values);
+			cells.swap(0, len - 1);
+			values.swap(0, len - 1);
+			assert_eq!(cells.unpack(len), values);
+		}
+	}
+}
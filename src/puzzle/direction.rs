@@ -0,0 +1,51 @@
+use std::fmt;
+
+/// Direction in which the empty cell moves, the moved tile going the opposite way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+	Up,
+	Down,
+	Left,
+	Right,
+}
+
+impl Direction {
+	/// All the directions, in the order used to generate neighbors.
+	pub const ALL: [Direction; 4] = [
+		Direction::Left,
+		Direction::Right,
+		Direction::Up,
+		Direction::Down,
+	];
+
+	/// Column and row offsets of the move.
+	pub fn offset(&self) -> (i32, i32) {
+		return match self {
+			Direction::Up => (0, -1),
+			Direction::Down => (0, 1),
+			Direction::Left => (-1, 0),
+			Direction::Right => (1, 0),
+		};
+	}
+
+	/// Direction cancelling this one.
+	pub fn opposite(&self) -> Self {
+		return match self {
+			Direction::Up => Direction::Down,
+			Direction::Down => Direction::Up,
+			Direction::Left => Direction::Right,
+			Direction::Right => Direction::Left,
+		};
+	}
+}
+
+impl fmt::Display for Direction {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		return match self {
+			Direction::Up => write!(f, "U"),
+			Direction::Down => write!(f, "D"),
+			Direction::Left => write!(f, "L"),
+			Direction::Right => write!(f, "R"),
+		};
+	}
+}
@@ -0,0 +1,87 @@
+use super::State;
+
+/// Solved state of a given number of columns and rows.
+pub type Goal = fn(usize, usize) -> State;
+
+impl crate::algorithm::Tool for Goal {
+	const DEFAULT: &'static str = "snail";
+	const STR_LIST: &'static [&'static str] = &["snail", "row_major", "blank_first"];
+	const FN_LIST: &'static [Self] = &[snail, row_major, blank_first];
+}
+
+/// Tiles spiraling inward from the top left corner, the empty cell ending the spiral.
+pub fn snail(width: usize, height: usize) -> State {
+	return State::goal_rectangle(width, height);
+}
+
+/// Tiles in reading order, the empty cell in the bottom right corner.
+pub fn row_major(width: usize, height: usize) -> State {
+	let count = width * height;
+	let cells = (1..=count).map(|val| (val % count) as u8).collect();
+	return State::new_rectangle(width, height, cells);
+}
+
+/// Tiles in reading order after the empty cell, in the top left corner.
+pub fn blank_first(width: usize, height: usize) -> State {
+	let cells = (0..width * height).map(|val| val as u8).collect();
+	return State::new_rectangle(width, height, cells);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::algorithm::{self, heuristic::tests::distances, Tool};
+
+	/// Every order of `values`.
+	fn permutations(values: Vec<u8>) -> Vec<Vec<u8>> {
+		if values.len() <= 1 {
+			return vec![values];
+		}
+		let mut permutations = Vec::new();
+		for (index, first) in values.iter().enumerate() {
+			let mut rest = values.clone();
+			rest.remove(index);
+			for mut permutation in self::permutations(rest) {
+				permutation.insert(0, *first);
+				permutations.push(permutation);
+			}
+		}
+		return permutations;
+	}
+
+	#[test]
+	fn rectangular_snails() {
+		assert_eq!(snail(4, 3).cells(), vec![1, 2, 3, 4, 10, 11, 0, 5, 9, 8, 7, 6]);
+		assert_eq!(snail(3, 4).cells(), vec![1, 2, 3, 10, 11, 4, 9, 0, 5, 8, 7, 6]);
+		assert_eq!(snail(2, 5).cells(), vec![1, 2, 0, 3, 9, 4, 8, 5, 7, 6]);
+	}
+
+	#[test]
+	fn goals_hold_every_tile_once() {
+		for goal in Goal::FN_LIST {
+			for width in 2..=10 {
+				for height in 2..=10 {
+					let mut cells = goal(width, height).cells();
+					cells.sort_unstable();
+					assert!(cells.iter().enumerate().all(|(i, value)| *value as usize == i));
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn solvability_follows_the_width_parity() {
+		for goal in Goal::FN_LIST {
+			for (width, height) in [(2, 3), (3, 2), (2, 4), (4, 2)] {
+				let goal = goal(width, height);
+				let reachable = distances(&goal);
+				let values = (0..goal.cell_count() as u8).collect();
+				for cells in permutations(values) {
+					let start = State::new_rectangle(width, height, cells);
+					let expected = reachable.contains_key(start.packed());
+					assert_eq!(algorithm::has_solution(&start, &goal), expected);
+				}
+			}
+		}
+	}
+}
@@ -1,5 +1,16 @@
+//! Puzzle states and solutions returned by the search algorithms.
+
+mod cells;
+pub use cells::Cells;
+
+mod direction;
+pub use direction::Direction;
+
+mod goal;
+pub use goal::Goal;
+
 mod solution;
-pub use solution::Solution;
+pub use solution::{Limit, Solution};
 
 mod state;
 pub use state::State;
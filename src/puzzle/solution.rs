@@ -1,20 +1,61 @@
 use std::collections::HashSet;
+use std::fmt;
 
+/// Result of a search: the moves found, if any, and the complexity of the search.
+#[derive(Clone)]
 pub struct Solution {
 	time_complexity: usize,
 	size_complexity: usize,
+	peak_frontier: usize,
 	moves: Option<Vec<super::State>>,
+	aborted: Option<Limit>,
+}
+
+/// Resource whose limit stopped a search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+	Time,
+	Nodes,
+	Memory,
+}
+
+impl fmt::Display for Limit {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		return match self {
+			Limit::Time => write!(f, "time"),
+			Limit::Nodes => write!(f, "nodes"),
+			Limit::Memory => write!(f, "memory"),
+		};
+	}
+}
+
+impl Default for Solution {
+	fn default() -> Self {
+		Solution::new()
+	}
 }
 
 impl Solution {
+	/// Empty solution, without moves and with no complexity.
 	pub fn new() -> Self {
 		Solution {
 			time_complexity: 0,
 			size_complexity: 0,
+			peak_frontier: 0,
 			moves: None,
+			aborted: None,
 		}
 	}
 
+	/// Mark the search as stopped by `limit`, keeping the complexity gathered so far.
+	pub fn abort(self, limit: Limit) -> Self {
+		return Solution {
+			aborted: Some(limit),
+			..self
+		};
+	}
+
+	/// Count one more expanded state, `current_size` being the number of states in memory.
 	pub fn update_complexity(&mut self, current_size: usize) {
 		self.time_complexity += 1;
 		if self.size_complexity < current_size {
@@ -22,18 +63,38 @@ impl Solution {
 		}
 	}
 
+	/// Record `frontier_size` states waiting to be expanded.
+	pub fn update_frontier(&mut self, frontier_size: usize) {
+		if self.peak_frontier < frontier_size {
+			self.peak_frontier = frontier_size;
+		}
+	}
+
+	/// Add the complexity of `other`, a search done as a part of this one.
+	pub fn add_complexity(&mut self, other: &Solution) {
+		self.time_complexity += other.time_complexity;
+		self.size_complexity = self.size_complexity.max(other.size_complexity);
+		self.peak_frontier = self.peak_frontier.max(other.peak_frontier);
+	}
+
+	/// Add the complexity of `other`, a search run at the same time as this one.
+	pub fn add_parallel_complexity(&mut self, other: &Solution) {
+		self.time_complexity += other.time_complexity;
+		self.size_complexity += other.size_complexity;
+		self.peak_frontier += other.peak_frontier;
+	}
+
+	/// Rebuild the moves from `last_state` by following the predecessors in `closed_set`.
 	pub fn build_solution(
 		self,
 		mut closed_set: HashSet<super::State>,
 		last_state: super::State,
 	) -> Self {
-		let time_complexity = self.time_complexity;
-		let size_complexity = self.size_complexity;
 		let mut moves = Vec::new();
 
 		let mut current_state = last_state;
 		while let Some(prev) = current_state.predecessor() {
-			let next = closed_set.take(prev);
+			let next = closed_set.take(&prev);
 			moves.push(current_state);
 			current_state = next.unwrap();
 		}
@@ -41,21 +102,56 @@ impl Solution {
 		let moves = Some(moves);
 
 		return Solution {
-			time_complexity,
-			size_complexity,
 			moves,
+			aborted: None,
+			..self
+		};
+	}
+
+	/// Solution whose moves are the states of `path`, from the start to the goal.
+	pub fn build_from_path(self, mut path: Vec<super::State>) -> Self {
+		path.reverse();
+		return Solution {
+			moves: Some(path),
+			aborted: None,
+			..self
 		};
 	}
 
+	/// States from the goal back to the start, `None` if no solution was found.
 	pub fn moves(&self) -> &Option<Vec<super::State>> {
 		&self.moves
 	}
 
+	/// Directions of the empty cell from the start to the goal, `None` if no solution was found.
+	pub fn directions(&self) -> Option<Vec<super::Direction>> {
+		let moves = self.moves.as_ref()?;
+		return Some(moves.iter().rev().filter_map(|state| state.moved()).collect());
+	}
+
+	/// Tiles moved from the start to the goal, `None` if no solution was found.
+	pub fn moved_tiles(&self) -> Option<Vec<u8>> {
+		let moves = self.moves.as_ref()?;
+		return Some(moves.iter().rev().filter_map(|state| state.moved_tile()).collect());
+	}
+
+	/// Limit that stopped the search before it ended, if any.
+	pub fn aborted(&self) -> Option<Limit> {
+		self.aborted
+	}
+
+	/// Number of states expanded during the search.
 	pub fn time_complexity(&self) -> usize {
 		self.time_complexity
 	}
 
+	/// Maximum number of states held in memory at the same time.
 	pub fn size_complexity(&self) -> usize {
 		self.size_complexity
 	}
+
+	/// Maximum number of states waiting to be expanded at the same time.
+	pub fn peak_frontier(&self) -> usize {
+		self.peak_frontier
+	}
 }
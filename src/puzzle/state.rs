@@ -1,3 +1,5 @@
+use super::{Cells, Direction};
+
 use colored::*;
 use rand::prelude::*;
 use std::borrow::Borrow;
@@ -6,136 +8,202 @@ use std::fmt;
 use std::hash::{self, Hash};
 use std::mem;
 
+/// A puzzle board, with the search informations attached to it.
+///
+/// The cells are packed and the predecessor is only known through the last move, so a state
+/// takes no more memory than needed. Equality and hashing only consider the board.
 #[derive(Debug, Clone)]
 pub struct State {
-	cells: Vec<u8>,
-	size: usize,
+	cells: Cells,
+	width: u8,
+	height: u8,
+	empty: u8,
 	cost: i32,
 	score: i32,
-	moved: Option<(i32, i32)>,
-	predecessor: Option<Vec<u8>>,
+	moved: Option<Direction>,
 }
 
-fn index(x: i32, y: i32, size: usize) -> usize {
-	return x as usize + y as usize * size;
+fn index(x: i32, y: i32, width: usize) -> usize {
+	return x as usize + y as usize * width;
 }
 
 impl State {
+	/// State of dimension `size` from its cells, listed row by row.
 	pub fn new(size: usize, cells: Vec<u8>) -> Self {
-		if cells.len() != size * size {
+		return State::new_rectangle(size, size, cells);
+	}
+
+	/// State of `width` columns and `height` rows from its cells, listed row by row.
+	pub fn new_rectangle(width: usize, height: usize, cells: Vec<u8>) -> Self {
+		if cells.len() != width * height {
 			panic!("Trying to create incorrectly sized state.")
 		}
+		let empty = cells.iter().position(|val| val == &0_u8).unwrap_or(0) as u8;
 		State {
-			cells,
-			size,
+			cells: Cells::pack(&cells),
+			width: width as u8,
+			height: height as u8,
+			empty,
 			cost: 0,
 			score: 0,
 			moved: None,
-			predecessor: None,
 		}
 	}
 
+	/// Solved state of dimension `size`, following the snail layout.
 	pub fn goal(size: usize) -> Self {
-		let mut cells = vec![0; size * size];
+		return State::goal_rectangle(size, size);
+	}
+
+	/// Solved state of `width` columns and `height` rows, following the snail layout.
+	pub fn goal_rectangle(width: usize, height: usize) -> Self {
+		let mut cells = vec![0; width * height];
 		let mut x = -1;
 		let mut y = 0;
 		let mut x_dir = 1;
 		let mut y_dir = 0;
 		let mut val = 0;
-		for n in 0..(size as i32) * 2 {
-			for _ in 0..(size as i32 - (n + 1) / 2) {
+		for n in 0..(width + height) as i32 {
+			let length = if n % 2 == 0 {
+				width as i32 - n / 2
+			} else {
+				height as i32 - 1 - n / 2
+			};
+			if length <= 0 {
+				break;
+			}
+			for _ in 0..length {
 				val += 1;
-				cells[index(x + x_dir, y + y_dir, size)] = val % (size * size) as u8;
+				cells[index(x + x_dir, y + y_dir, width)] = (val % (width * height)) as u8;
 				x += x_dir;
 				y += y_dir;
 			}
 			mem::swap(&mut x_dir, &mut y_dir);
 			x_dir *= -1;
 		}
-		State {
-			cells,
-			size,
-			cost: 0,
-			score: 0,
-			moved: None,
-			predecessor: None,
-		}
+		return State::new_rectangle(width, height, cells);
 	}
 
+	/// All the states reachable by moving one tile into the empty cell.
 	pub fn neighbors(&self) -> Vec<Self> {
-		let mut neighbors = Vec::new();
-		let index_empty_cell = self.cells.iter().position(|val| val == &0_u8).unwrap();
-		let x_empty_cell = (index_empty_cell % self.size) as i32;
-		let y_empty_cell = (index_empty_cell / self.size) as i32;
-
-		if x_empty_cell > 0 {
-			neighbors.push(self.neighbor(x_empty_cell, y_empty_cell, -1, 0));
-		}
-		if x_empty_cell < (self.size - 1) as i32 {
-			neighbors.push(self.neighbor(x_empty_cell, y_empty_cell, 1, 0));
-		}
-		if y_empty_cell > 0 {
-			neighbors.push(self.neighbor(x_empty_cell, y_empty_cell, 0, -1));
-		}
-		if y_empty_cell < (self.size - 1) as i32 {
-			neighbors.push(self.neighbor(x_empty_cell, y_empty_cell, 0, 1));
-		}
-		return neighbors;
+		return Direction::ALL
+			.iter()
+			.filter_map(|direction| self.neighbor(*direction))
+			.collect();
 	}
 
-	fn neighbor(&self, x_empty: i32, y_empty: i32, x_neighbor: i32, y_neighbor: i32) -> State {
+	/// State reached by moving the empty cell in `direction`, if it stays on the board.
+	pub fn neighbor(&self, direction: Direction) -> Option<State> {
+		let (x_empty, y_empty) = self.coord_of_index(self.empty as usize);
+		let (dx, dy) = direction.offset();
+		let (x_next, y_next) = (x_empty + dx, y_empty + dy);
+		if x_next < 0 || x_next >= self.width as i32 || y_next < 0 || y_next >= self.height as i32 {
+			return None;
+		}
+		let next_empty = index(x_next, y_next, self.width());
 		let mut cells = self.cells.clone();
+		cells.swap(self.empty as usize, next_empty);
 
-		cells.swap(
-			index(x_empty, y_empty, self.size),
-			index(x_empty + x_neighbor, y_empty + y_neighbor, self.size),
-		);
-
-		return State {
+		return Some(State {
 			cells,
-			size: self.size,
+			width: self.width,
+			height: self.height,
+			empty: next_empty as u8,
 			cost: 0,
 			score: 0,
-			moved: Some((x_empty, y_empty)),
-			predecessor: Some(self.cells.clone()),
-		};
+			moved: Some(direction),
+		});
 	}
 
-	pub fn shuffle(&mut self) {
+	/// Move a tile picked by `rng` into the empty cell.
+	pub fn shuffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
 		let mut neighbors = self.neighbors();
-		let choice = random::<usize>() % neighbors.len();
-		self.cells = neighbors.swap_remove(choice).cells;
+		let choice = rng.gen_range(0..neighbors.len());
+		let next = neighbors.swap_remove(choice);
+		self.cells = next.cells;
+		self.empty = next.empty;
 	}
 
+	/// Swap the two first tiles, changing whether the state is solvable.
 	pub fn corrupt(&mut self) {
-		if self.cells[0] == 0 || self.cells[1] == 0 {
-			self.cells.swap(self.size - 1, self.size - 2);
-		} else {
-			self.cells.swap(0, 1);
-		}
+		let tiles: Vec<usize> = (0..self.cell_count())
+			.filter(|index| *index != self.empty as usize)
+			.take(2)
+			.collect();
+		self.cells.swap(tiles[0], tiles[1]);
+	}
+
+	/// Direction in which the empty cell moved to reach this state.
+	pub fn moved(&self) -> Option<Direction> {
+		self.moved
+	}
+
+	/// Column and row of the tile moved to reach this state.
+	pub fn moved_coord(&self) -> Option<(i32, i32)> {
+		let direction = self.moved?;
+		let (x_empty, y_empty) = self.coord_of_index(self.empty as usize);
+		let (dx, dy) = direction.offset();
+		return Some((x_empty - dx, y_empty - dy));
+	}
+
+	/// Tile moved to reach this state.
+	pub fn moved_tile(&self) -> Option<u8> {
+		let (x, y) = self.moved_coord()?;
+		return Some(self.access(x, y));
 	}
 
-	pub fn predecessor(&self) -> &Option<Vec<u8>> {
-		&self.predecessor
+	/// Cells of the state this one was reached from.
+	pub fn predecessor(&self) -> Option<Cells> {
+		let direction = self.moved?;
+		let (x_empty, y_empty) = self.coord_of_index(self.empty as usize);
+		let (dx, dy) = direction.offset();
+		let mut cells = self.cells.clone();
+		cells.swap(self.empty as usize, index(x_empty - dx, y_empty - dy, self.width()));
+		return Some(cells);
+	}
+
+	/// Number of columns of the board.
+	pub fn width(&self) -> usize {
+		self.width as usize
+	}
+
+	/// Number of rows of the board.
+	pub fn height(&self) -> usize {
+		self.height as usize
 	}
 
-	pub fn size(&self) -> usize {
-		self.size
+	/// Number of cells of the board.
+	pub fn cell_count(&self) -> usize {
+		self.width() * self.height()
 	}
 
-	pub fn cells(&self) -> &Vec<u8> {
+	/// Cells of the board, row by row.
+	pub fn cells(&self) -> Vec<u8> {
+		self.cells.unpack(self.cell_count())
+	}
+
+	/// Packed cells of the board, identifying the state.
+	pub fn packed(&self) -> &Cells {
 		&self.cells
 	}
 
+	/// Value of the cell at column `x` and row `y`.
 	pub fn access(&self, x: i32, y: i32) -> u8 {
-		self.cells[index(x, y, self.size)]
+		self.cells.get(index(x, y, self.width()))
 	}
 
+	/// Column and row of the cell holding `value`.
 	pub fn coord(&self, value: u8) -> (i32, i32) {
-		let index = self.cells.iter().position(|val| val == &value).unwrap();
-		return ((index % self.size) as i32, (index / self.size) as i32);
+		let index = self.cells.position(value, self.cell_count()).unwrap();
+		return self.coord_of_index(index);
+	}
+
+	fn coord_of_index(&self, index: usize) -> (i32, i32) {
+		let width = self.width();
+		return ((index % width) as i32, (index / width) as i32);
 	}
 
+	/// Number of moves done from the start.
 	pub fn cost(&self) -> &i32 {
 		&self.cost
 	}
@@ -144,7 +212,8 @@ impl State {
 		&mut self.cost
 	}
 
-	pub fn _score(&self) -> &i32 {
+	/// Score given to the state by the search type when it was reached.
+	pub fn score(&self) -> &i32 {
 		&self.score
 	}
 
@@ -152,17 +221,19 @@ impl State {
 		&mut self.score
 	}
 
+	/// Number of tile pairs ordered differently than in `goal`.
 	pub fn count_inversion(&self, goal: &Self) -> i32 {
 		let mut inv = 0;
+		let cells = self.cells();
 
-		for (i, fake_val) in self.cells.iter().enumerate() {
+		for (i, fake_val) in cells.iter().enumerate() {
 			if *fake_val != 0 {
 				let (x_i, y_i) = goal.coord(*fake_val);
-				let val_i = index(x_i, y_i, self.size);
-				for j in 0..i {
-					let (x_j, y_j) = goal.coord(self.cells[j]);
-					let val_j = index(x_j, y_j, self.size);
-					if self.cells[j] > 0 && val_j > val_i {
+				let val_i = index(x_i, y_i, self.width());
+				for other_val in cells[..i].iter() {
+					let (x_j, y_j) = goal.coord(*other_val);
+					let val_j = index(x_j, y_j, self.width());
+					if *other_val > 0 && val_j > val_i {
 						inv += 1;
 					}
 				}
@@ -171,6 +242,7 @@ impl State {
 		return inv;
 	}
 
+	/// Number of rows between the empty cell and its position in `goal`.
 	pub fn row_of_empty(&self, goal: &Self) -> i32 {
 		let (_, y0) = self.coord(0);
 		let (_, y1) = goal.coord(0);
@@ -186,14 +258,14 @@ impl Hash for State {
 
 impl PartialEq for State {
 	fn eq(&self, other: &Self) -> bool {
-		self.cells == other.cells
+		self.width == other.width && self.height == other.height && self.cells == other.cells
 	}
 }
 
 impl Eq for State {}
 
-impl Borrow<Vec<u8>> for State {
-	fn borrow(&self) -> &Vec<u8> {
+impl Borrow<Cells> for State {
+	fn borrow(&self) -> &Cells {
 		&self.cells
 	}
 }
@@ -201,11 +273,12 @@ impl Borrow<Vec<u8>> for State {
 impl fmt::Display for State {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		let mut output = String::new();
-		for i in 0..self.size as i32 {
+		let moved = self.moved_coord();
+		for i in 0..self.height as i32 {
 			output = format!("{}{}", output, "\n|".dimmed());
-			for j in 0..self.size as i32 {
+			for j in 0..self.width as i32 {
 				let value = self.access(j, i);
-				let value_colored = match self.moved {
+				let value_colored = match moved {
 					Some((x, y)) if x == j && y == i || value == 0 => format!("{}", value).green(),
 					_ => format!("{}", value).white(),
 				};
@@ -213,7 +286,7 @@ impl fmt::Display for State {
 					"{} {:^size$} ",
 					output,
 					value_colored,
-					size = if self.size > 3 { 3 } else { 1 }
+					size = if self.cell_count() > 9 { 3 } else { 1 }
 				)
 			}
 			output = format!("{}{}", output, "|".dimmed());
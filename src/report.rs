@@ -0,0 +1,96 @@
+use n_puzzle::puzzle;
+
+use std::time;
+
+/// Search settings reported along with the solution.
+pub struct Settings<'a> {
+	pub algorithm: &'a str,
+	pub heuristic: &'a str,
+	pub search_type: &'a str,
+	pub weight: f32,
+	pub seed: Option<u64>,
+}
+
+/// JSON document describing the puzzle, the settings and the result of its search.
+///
+/// `solution` is `None` when the puzzle is unsolvable, the search being skipped. `bound` is the
+/// last bound proven on how many times longer than the optimal one the solution can be, only
+/// known for the anytime search.
+pub fn json(
+	start: &puzzle::State,
+	goal: &puzzle::State,
+	settings: &Settings,
+	solution: Option<&puzzle::Solution>,
+	bound: Option<f32>,
+	wall_time: time::Duration,
+) -> String {
+	let status = match solution {
+		None => "unsolvable",
+		Some(s) if s.aborted().is_some() => "aborted",
+		Some(s) if s.moves().is_some() => "solved",
+		Some(_) => "unsolvable",
+	};
+	let aborted = solution
+		.and_then(|s| s.aborted())
+		.map_or(String::from("null"), |limit| string(&limit.to_string()));
+	let directions = solution.and_then(|s| s.directions());
+	let moves = directions.as_ref().map_or(String::from("null"), |directions| {
+		let list: Vec<String> = directions.iter().map(|d| string(&d.to_string())).collect();
+		format!("[{}]", list.join(", "))
+	});
+	let move_count = directions
+		.as_ref()
+		.map_or(String::from("null"), |directions| directions.len().to_string());
+	let fields = [
+		("rows", start.height().to_string()),
+		("cols", start.width().to_string()),
+		("start", board(start)),
+		("goal", board(goal)),
+		("seed", settings.seed.map_or(String::from("null"), |seed| seed.to_string())),
+		("solvable", solution.is_some().to_string()),
+		("status", string(status)),
+		("aborted", aborted),
+		("moves", moves),
+		("move_count", move_count),
+		("bound", bound.map_or(String::from("null"), |bound| bound.to_string())),
+		("algorithm", string(settings.algorithm)),
+		("heuristic", string(settings.heuristic)),
+		("search_type", string(settings.search_type)),
+		("weight", settings.weight.to_string()),
+		("time_complexity", solution.map_or(0, |s| s.time_complexity()).to_string()),
+		("size_complexity", solution.map_or(0, |s| s.size_complexity()).to_string()),
+		("wall_time", wall_time.as_secs_f64().to_string()),
+	];
+	let members: Vec<String> = fields
+		.iter()
+		.map(|(key, value)| format!("\t{}: {}", string(key), value))
+		.collect();
+	return format!("{{\n{}\n}}", members.join(",\n"));
+}
+
+fn board(state: &puzzle::State) -> String {
+	let cells = state.cells();
+	let rows: Vec<String> = cells
+		.chunks(state.width())
+		.map(|row| {
+			let values: Vec<String> = row.iter().map(|v| v.to_string()).collect();
+			format!("[{}]", values.join(", "))
+		})
+		.collect();
+	return format!("[{}]", rows.join(", "));
+}
+
+fn string(raw: &str) -> String {
+	let mut escaped = String::with_capacity(raw.len() + 2);
+	escaped.push('"');
+	for c in raw.chars() {
+		match c {
+			'"' => escaped.push_str("\\\""),
+			'\\' => escaped.push_str("\\\\"),
+			c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+			c => escaped.push(c),
+		}
+	}
+	escaped.push('"');
+	return escaped;
+}
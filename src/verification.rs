@@ -0,0 +1,143 @@
+//! Replay of a move sequence found elsewhere, checking it solves a puzzle.
+
+use crate::puzzle;
+
+use std::error;
+use std::fmt;
+
+/// A move to replay, given as the direction of the empty cell or as the number of the moved tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+	Direction(puzzle::Direction),
+	Tile(u8),
+}
+
+impl fmt::Display for Move {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		return match self {
+			Move::Direction(direction) => write!(f, "{}", direction),
+			Move::Tile(tile) => write!(f, "{}", tile),
+		};
+	}
+}
+
+/// Outcome of a replay that only made legal moves.
+pub struct Replay {
+	pub last_state: puzzle::State,
+	pub move_count: usize,
+	pub solved: bool,
+}
+
+/// Parse the moves of `raw`, as printed by the moves, tiles or json outputs.
+///
+/// Directions are the letters U, D, L and R, which can be written without separators, and
+/// numbers are moved tiles. Whitespaces, commas, quotes and brackets separate the moves.
+pub fn parse(raw: &str) -> Result<Vec<Move>, Error> {
+	let mut moves = Vec::new();
+	let separators = |c: char| c.is_whitespace() || ",\"[]".contains(c);
+	for token in raw.split(separators).filter(|token| !token.is_empty()) {
+		if let Ok(tile) = token.parse() {
+			moves.push(Move::Tile(tile));
+			continue;
+		}
+		for letter in token.chars() {
+			let direction = match letter.to_ascii_uppercase() {
+				'U' => puzzle::Direction::Up,
+				'D' => puzzle::Direction::Down,
+				'L' => puzzle::Direction::Left,
+				'R' => puzzle::Direction::Right,
+				_ => return Err(Error::InvalidMove(token.to_string())),
+			};
+			moves.push(Move::Direction(direction));
+		}
+	}
+	return Ok(moves);
+}
+
+/// Play `moves` from `start`, stopping at the first one that can't be made.
+pub fn replay(
+	start: &puzzle::State,
+	goal: &puzzle::State,
+	moves: &[Move],
+) -> Result<Replay, Error> {
+	let mut state = start.clone();
+	for (index, next_move) in moves.iter().enumerate() {
+		let next = state.neighbors().into_iter().find(|neighbor| match next_move {
+			Move::Direction(direction) => neighbor.moved() == Some(*direction),
+			Move::Tile(tile) => neighbor.moved_tile() == Some(*tile),
+		});
+		state = next.ok_or(Error::IllegalMove(index + 1, *next_move))?;
+	}
+	return Ok(Replay {
+		solved: &state == goal,
+		last_state: state,
+		move_count: moves.len(),
+	});
+}
+
+/// Errors that can occur while verifying a move sequence.
+#[derive(Debug)]
+pub enum Error {
+	InvalidMove(String),
+	IllegalMove(usize, Move),
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		return match self {
+			Error::InvalidMove(token) => write!(
+				f,
+				"Invalid move could not be parsed: {}, expected U, D, L, R or a tile number",
+				token
+			),
+			Error::IllegalMove(step, illegal) => {
+				write!(f, "Illegal move at step {}: {} can not be played", step, illegal)
+			}
+		};
+	}
+}
+
+impl error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use puzzle::Direction::{Down, Left, Right, Up};
+
+	#[test]
+	fn parses_directions_and_tiles() {
+		let directions = vec![Up, Down, Left, Right, Up].into_iter().map(Move::Direction);
+		assert_eq!(parse("UDLR u").unwrap(), directions.collect::<Vec<Move>>());
+		let mixed = vec![Move::Tile(12), Move::Direction(Left), Move::Tile(3)];
+		assert_eq!(parse("[\"12\", \"L\", 3]").unwrap(), mixed);
+		assert!(matches!(parse("U UX"), Err(Error::InvalidMove(token)) if token == "UX"));
+	}
+
+	#[test]
+	fn replays_legal_moves() {
+		let goal = puzzle::State::goal(3);
+		let moves = parse("U D L R").unwrap();
+		let replay = replay(&goal, &goal, &moves).unwrap();
+		assert!(replay.solved);
+		assert_eq!(replay.move_count, 4);
+		assert_eq!(replay.last_state, goal);
+
+		let start = goal.neighbor(Up).unwrap().neighbor(Right).unwrap();
+		let replay = super::replay(&start, &goal, &parse("3 2").unwrap()).unwrap();
+		assert!(replay.solved);
+		let replay = super::replay(&start, &goal, &parse("3").unwrap()).unwrap();
+		assert!(!replay.solved);
+		assert_eq!(replay.last_state, goal.neighbor(Up).unwrap());
+	}
+
+	#[test]
+	fn rejects_illegal_moves() {
+		let goal = puzzle::State::goal(3);
+		let illegal = replay(&goal, &goal, &parse("U U").unwrap());
+		assert!(matches!(illegal, Err(Error::IllegalMove(2, Move::Direction(Up)))));
+		let illegal = replay(&goal, &goal, &parse("2 4").unwrap());
+		assert!(matches!(illegal, Err(Error::IllegalMove(2, Move::Tile(4)))));
+		let illegal = replay(&goal, &goal, &parse("9").unwrap());
+		assert!(matches!(illegal, Err(Error::IllegalMove(1, Move::Tile(9)))));
+	}
+}